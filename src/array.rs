@@ -0,0 +1,305 @@
+use core::mem::MaybeUninit;
+use core::ops::Range;
+use core::ptr;
+
+use super::*;
+
+/// A ring buffer stored inline in a `[MaybeUninit<T>; N]`, without any heap allocation.
+///
+/// Like `Buffer`, pushing into a full buffer overwrites the oldest element, and `N == 0`
+/// is a sink. Index wrapping compiles down to a mask when `N` is a power of two.
+pub struct ArrayBuffer<T, const N: usize> {
+    buffer: [MaybeUninit<T>; N],
+    len: usize,
+    pos: usize,
+}
+
+impl<T, const N: usize> ArrayBuffer<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+            pos: 0,
+        }
+    }
+    // `index` is at most N
+    fn wrap(index: usize) -> usize {
+        if N.is_power_of_two() {
+            index & (N - 1)
+        } else if index == N {
+            0
+        } else {
+            index
+        }
+    }
+    // physical index of the oldest element
+    fn tail(&self) -> usize {
+        if N.is_power_of_two() {
+            self.pos.wrapping_sub(self.len) & (N - 1)
+        } else if self.len <= self.pos {
+            self.pos - self.len
+        } else {
+            self.pos + N - self.len
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn capacity(&self) -> usize {
+        N
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        FillLevel::new(self.len, N)
+    }
+    pub fn push(&mut self, value: T) {
+        if N == 0 {
+            return;
+        }
+        let slot = &mut self.buffer[self.pos];
+        let evicted = if self.len == N {
+            // SAFETY: a full buffer has every slot initialized
+            Some(unsafe { slot.assume_init_read() })
+        } else {
+            self.len += 1;
+            None
+        };
+        slot.write(value);
+        self.pos = Self::wrap(self.pos + 1);
+        // dropped last, so a panicking `Drop` leaves the buffer consistent
+        drop(evicted);
+    }
+    /// Like `head`, but borrows the newest element instead of cloning it.
+    pub fn head_ref(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+    // physical ranges of the (older, newer) halves
+    fn ranges(&self) -> (Range<usize>, Range<usize>) {
+        let tail = self.tail();
+        if tail < self.pos || self.is_empty() {
+            (tail..self.pos, 0..0)
+        } else {
+            (tail..N, 0..self.pos)
+        }
+    }
+    /// The contents as (older, newer) halves; their concatenation is oldest to newest.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (older, newer) = self.ranges();
+        let (older, newer) = (&self.buffer[older], &self.buffer[newer]);
+        // SAFETY: the slots between tail and pos are initialized
+        unsafe {
+            (
+                &*(older as *const [MaybeUninit<T>] as *const [T]),
+                &*(newer as *const [MaybeUninit<T>] as *const [T]),
+            )
+        }
+    }
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (older, newer) = self.as_slices();
+        Iter::new(older, newer)
+    }
+    pub fn clear(&mut self) {
+        let (older, newer) = self.ranges();
+        self.len = 0;
+        self.pos = 0;
+        // SAFETY: both ranges were initialized and are no longer tracked by `len`
+        unsafe {
+            ptr::drop_in_place(&mut self.buffer[older] as *mut [MaybeUninit<T>] as *mut [T]);
+            ptr::drop_in_place(&mut self.buffer[newer] as *mut [MaybeUninit<T>] as *mut [T]);
+        }
+    }
+}
+impl<T: Clone, const N: usize> ArrayBuffer<T, N> {
+    /// Overwrites the oldest elements like `push`, so every value is accepted and the
+    /// returned count is `slice.len()`, as with `Buffer::push_slice`.
+    pub fn push_slice(&mut self, slice: &[T]) -> usize {
+        // only the last N values survive
+        for value in &slice[slice.len().saturating_sub(N)..] {
+            self.push(value.clone());
+        }
+        slice.len()
+    }
+    pub fn head(&self) -> Option<T> {
+        self.head_ref().cloned()
+    }
+    #[cfg(feature = "alloc")]
+    pub fn snapshot(&self) -> Vec<T> {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T, const N: usize> Default for ArrayBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayBuffer<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// An `ArrayBuffer` behind a read-write lock, usable from a `static`.
+///
+/// Without atomic compare-and-swap there is no `DefaultLock`, so the lock `L` has to be
+/// supplied, for example one built on a critical section.
+pub struct SyncArrayBuffer<
+    T,
+    const N: usize,
+    #[cfg(target_has_atomic = "ptr")] L = DefaultLock,
+    #[cfg(not(target_has_atomic = "ptr"))] L,
+> {
+    buffer: Lock<L, ArrayBuffer<T, N>>,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, const N: usize> SyncArrayBuffer<T, N> {
+    pub const fn new() -> Self {
+        Self::with_lock()
+    }
+}
+impl<T, const N: usize, L: RawLock> SyncArrayBuffer<T, N, L> {
+    /// Like `new`, but guarded by the lock `L` instead of `DefaultLock`.
+    pub const fn with_lock() -> Self {
+        Self {
+            buffer: Lock::new(ArrayBuffer::new()),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.buffer.read().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.buffer.read().is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        N
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        self.buffer.read().fill_level()
+    }
+    pub fn push(&self, value: T) {
+        self.buffer.write().push(value)
+    }
+    pub fn clear(&self) {
+        self.buffer.write().clear()
+    }
+}
+impl<T: Clone, const N: usize, L: RawLock> SyncArrayBuffer<T, N, L> {
+    pub fn push_slice(&self, slice: &[T]) -> usize {
+        self.buffer.write().push_slice(slice)
+    }
+    pub fn head(&self) -> Option<T> {
+        self.buffer.read().head()
+    }
+    #[cfg(feature = "alloc")]
+    pub fn snapshot(&self) -> Vec<T> {
+        self.buffer.read().snapshot()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, const N: usize> Default for SyncArrayBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let mut buffer = ArrayBuffer::<_, 3>::new();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.snapshot(), vec![]);
+
+        buffer.push(1);
+        assert_eq!(buffer.snapshot(), vec![1]);
+        assert_eq!(buffer.fill_level(), FillLevel::Partial);
+
+        buffer.push_slice(&[2, 3]);
+        assert_eq!(buffer.snapshot(), vec![1, 2, 3]);
+        assert_eq!(buffer.fill_level(), FillLevel::Full);
+
+        assert_eq!(buffer.push_slice(&[4, 5]), 2);
+        assert_eq!(buffer.snapshot(), vec![3, 4, 5]);
+        assert_eq!(buffer.head(), Some(5));
+        assert_eq!(buffer.head_ref(), Some(&5));
+
+        buffer.clear();
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn power_of_two() {
+        let mut buffer = ArrayBuffer::<_, 4>::new();
+        buffer.push_slice(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(buffer.snapshot(), vec![3, 4, 5, 6]);
+        assert_eq!(
+            buffer.iter().rev().copied().collect::<Vec<_>>(),
+            vec![6, 5, 4, 3]
+        );
+        buffer.push_slice(&(7..20).collect::<Vec<_>>());
+        assert_eq!(buffer.snapshot(), vec![16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut buffer = ArrayBuffer::<_, 0>::new();
+        buffer.push(1);
+        assert_eq!(buffer.push_slice(&[2, 3]), 2);
+        assert_eq!(buffer.fill_level(), FillLevel::Empty);
+        assert_eq!(buffer.head(), None);
+    }
+
+    #[test]
+    fn drops() {
+        let value = Arc::new(());
+        let mut buffer = ArrayBuffer::<_, 2>::new();
+        buffer.push_slice(&[value.clone(), value.clone(), value.clone()]);
+        assert_eq!(Arc::strong_count(&value), 3);
+        drop(buffer);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn panicking_drop() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct D(u32);
+        impl Drop for D {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+                if self.0 == 0 {
+                    panic!("drop");
+                }
+            }
+        }
+
+        let mut buffer = ArrayBuffer::<_, 1>::new();
+        buffer.push(D(0));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| buffer.push(D(1))));
+        assert!(result.is_err());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+        assert_eq!(buffer.head_ref().map(|d| d.0), Some(1));
+        drop(buffer);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn sync() {
+        static BUFFER: SyncArrayBuffer<u32, 3> = SyncArrayBuffer::new();
+
+        BUFFER.push(1);
+        assert_eq!(BUFFER.push_slice(&[2, 3, 4]), 3);
+        assert_eq!(BUFFER.snapshot(), vec![2, 3, 4]);
+        assert_eq!(BUFFER.head(), Some(4));
+        assert_eq!(BUFFER.fill_level(), FillLevel::Full);
+        BUFFER.clear();
+        assert!(BUFFER.is_empty());
+    }
+}
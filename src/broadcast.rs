@@ -0,0 +1,196 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use super::*;
+#[cfg(feature = "std")]
+use crate::wait::deadline;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// Nothing new has been pushed yet. Only returned by `try_recv`.
+    Empty,
+    /// Only returned by `recv_timeout`.
+    Timeout,
+    /// The subscriber fell behind and this many values were overwritten or removed before it
+    /// could read them. The next call continues with the oldest value still buffered.
+    Lagged(u64),
+    /// Every writer is gone and all values have been received.
+    Closed,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Empty => f.write_str("no new value in buffer"),
+            RecvError::Timeout => f.write_str("timed out waiting for a new value"),
+            RecvError::Lagged(n) => write!(f, "subscriber lagged behind by {n} values"),
+            RecvError::Closed => f.write_str("all writers are gone"),
+        }
+    }
+}
+
+impl core::error::Error for RecvError {}
+
+/// Receives every value pushed after it subscribed, at its own pace.
+///
+/// Subscribers never hold the producer back: a subscriber that is lapped by the writer gets
+/// `RecvError::Lagged` and resumes at the oldest value still buffered.
+pub struct Subscriber<T, L: RawLock = DefaultLock> {
+    reader: Reader<T, L>,
+    // sequence number of the next value to receive
+    next: u64,
+}
+
+impl<T: Clone, L: RawLock> Subscriber<T, L> {
+    fn new(reader: Reader<T, L>) -> Self {
+        let next = reader.shared.read().pushed();
+        Self { reader, next }
+    }
+    pub fn try_recv(&mut self) -> Result<T, RecvError> {
+        poll(&self.reader, &mut self.next)
+    }
+    #[cfg(feature = "std")]
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        self.recv_deadline(None)
+    }
+    #[cfg(feature = "std")]
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvError> {
+        self.recv_deadline(deadline(timeout))
+    }
+    #[cfg(feature = "std")]
+    fn recv_deadline(&mut self, deadline: Option<Instant>) -> Result<T, RecvError> {
+        let Subscriber { reader, next } = self;
+        let received = reader.shared.wait_for(
+            || match poll(reader, next) {
+                Err(RecvError::Empty) => None,
+                received => Some(received),
+            },
+            deadline,
+        );
+        received.unwrap_or(Err(RecvError::Timeout))
+    }
+    /// Number of buffered values this subscriber has not received yet.
+    pub fn len(&self) -> usize {
+        let buffer = self.reader.shared.read();
+        buffer.len() - buffer.position(self.next)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn poll<T: Clone, L: RawLock>(reader: &Reader<T, L>, next: &mut u64) -> Result<T, RecvError> {
+    // checked first, so values pushed by the last writer are still received
+    let closed = reader.is_closed();
+    let buffer = reader.shared.read();
+    let index = buffer.position(*next);
+    match buffer.seq(index) {
+        Some(seq) if seq > *next => {
+            let lagged = seq - *next;
+            *next = seq;
+            Err(RecvError::Lagged(lagged))
+        }
+        Some(seq) => {
+            *next = seq + 1;
+            Ok(buffer.get(index).unwrap().clone())
+        }
+        None if buffer.pushed() > *next => {
+            let lagged = buffer.pushed() - *next;
+            *next = buffer.pushed();
+            Err(RecvError::Lagged(lagged))
+        }
+        None if closed => Err(RecvError::Closed),
+        None => Err(RecvError::Empty),
+    }
+}
+
+impl<T, L: RawLock> Clone for Subscriber<T, L> {
+    fn clone(&self) -> Self {
+        Self {
+            reader: self.reader.clone(),
+            next: self.next,
+        }
+    }
+}
+
+impl<T: Clone, L: RawLock> Buffer<T, L> {
+    pub fn subscribe(&self) -> Subscriber<T, L> {
+        Subscriber::new(self.reader())
+    }
+}
+
+impl<T: Clone, L: RawLock> Reader<T, L> {
+    pub fn subscribe(&self) -> Subscriber<T, L> {
+        Subscriber::new(self.clone())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn try_recv() {
+        let buffer = Buffer::new(3);
+        buffer.push(0);
+        let mut fast = buffer.subscribe();
+        let mut slow = buffer.subscribe();
+        assert_eq!(fast.try_recv(), Err(RecvError::Empty));
+
+        buffer.push_slice(&[1, 2]);
+        assert_eq!(fast.len(), 2);
+        assert_eq!(fast.try_recv(), Ok(1));
+        assert_eq!(fast.try_recv(), Ok(2));
+        assert_eq!(fast.try_recv(), Err(RecvError::Empty));
+
+        buffer.push_slice(&[3, 4, 5]);
+        assert_eq!(fast.try_recv(), Ok(3));
+        assert_eq!(slow.try_recv(), Err(RecvError::Lagged(2)));
+        assert_eq!(slow.try_recv(), Ok(3));
+        assert_eq!(slow.try_recv(), Ok(4));
+
+        drop(buffer);
+        assert_eq!(slow.try_recv(), Ok(5));
+        assert_eq!(slow.try_recv(), Err(RecvError::Closed));
+        assert_eq!(fast.try_recv(), Ok(4));
+    }
+
+    #[test]
+    fn recv() {
+        let buffer = Buffer::new(64);
+        let consumers: Vec<_> = (0..3)
+            .map(|_| {
+                let mut subscriber = buffer.subscribe();
+                thread::spawn(move || {
+                    let mut received = vec![];
+                    while let Ok(value) = subscriber.recv() {
+                        received.push(value);
+                    }
+                    received
+                })
+            })
+            .collect();
+
+        for value in 0..50 {
+            buffer.push(value);
+        }
+        drop(buffer);
+        for consumer in consumers {
+            assert_eq!(consumer.join().unwrap(), (0..50).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn recv_timeout() {
+        let buffer = Buffer::new(2);
+        let mut subscriber = buffer.subscribe();
+        assert_eq!(
+            subscriber.recv_timeout(Duration::from_millis(10)),
+            Err(RecvError::Timeout)
+        );
+        buffer.push(1);
+        assert_eq!(subscriber.recv_timeout(Duration::from_millis(10)), Ok(1));
+    }
+}
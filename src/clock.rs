@@ -0,0 +1,332 @@
+use core::ops::RangeBounds;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::AtomicU64;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use super::*;
+use crate::lock::SharedGuard;
+
+/// A source of timestamps for buffers with time-based retention.
+pub trait Clock: Send + Sync + 'static {
+    /// Time since an arbitrary fixed point. Must never go backwards.
+    fn now(&self) -> Duration;
+}
+
+/// Monotonic time since the clock was created.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic tests. Clones share the same time.
+///
+/// Only on targets with 64-bit atomics.
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn advance(&self, by: Duration) {
+        self.nanos
+            .fetch_add(by.as_nanos() as u64, Ordering::Release);
+    }
+    /// Panics if `now` is earlier than the current time.
+    pub fn set(&self, now: Duration) {
+        let previous = self.nanos.swap(now.as_nanos() as u64, Ordering::Release);
+        assert!(
+            previous <= now.as_nanos() as u64,
+            "clock must not go backwards"
+        );
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Acquire))
+    }
+}
+
+impl<T, L: RawLock> Shared<T, L> {
+    pub(crate) fn now(&self) -> Duration {
+        self.clock
+            .as_ref()
+            .map_or(Duration::ZERO, |clock| clock.now())
+    }
+    // values pushed before the cutoff have expired
+    fn cutoff(&self) -> Option<Duration> {
+        self.now().checked_sub(self.max_age?)
+    }
+    // number of the oldest values in `buffer` that have expired
+    pub(crate) fn expired(&self, buffer: &buffer::Buffer<T>) -> usize {
+        self.cutoff().map_or(0, |cutoff| buffer.expired(cutoff))
+    }
+    // under the write lock; the expired values are only kept if `on_evict` wants them
+    pub(crate) fn expire_locked(
+        &self,
+        buffer: &mut buffer::Buffer<T>,
+        evicted: &mut Vec<T>,
+    ) -> usize {
+        let expired = self.expired(buffer);
+        for _ in 0..expired {
+            let value = buffer.pop_front();
+            evicted.extend(value.filter(|_| self.hooks.evicts()));
+        }
+        buffer.stats.expire(expired);
+        expired
+    }
+    pub(crate) fn expire(&self) -> usize {
+        let mut evicted = Vec::new();
+        let expired = self.update(|buffer| self.expire_locked(buffer, &mut evicted));
+        self.hooks.evict(evicted);
+        expired
+    }
+    // like `update`, after dropping expired values
+    pub(crate) fn update_live<R>(&self, f: impl FnOnce(&mut buffer::Buffer<T>) -> R) -> R {
+        let mut evicted = Vec::new();
+        let result = self.update(|buffer| {
+            self.expire_locked(buffer, &mut evicted);
+            f(buffer)
+        });
+        self.hooks.evict(evicted);
+        result
+    }
+    // the read lock, with expired values hidden rather than removed
+    pub(crate) fn read(&self) -> Live<'_, T, L> {
+        let buffer = self.buffer.read();
+        let skip = self.expired(&buffer);
+        Live { buffer, skip }
+    }
+}
+
+/// The buffer contents under the read lock, without the values that have expired.
+///
+/// Expired values stay in the buffer until something takes the write lock to remove them, so
+/// every reader goes through this view instead of the raw buffer.
+pub(crate) struct Live<'a, T, L: RawLock> {
+    pub(crate) buffer: SharedGuard<'a, L, buffer::Buffer<T>>,
+    // expired values at the front of `buffer`
+    pub(crate) skip: usize,
+}
+
+impl<T, L: RawLock> Live<'_, T, L> {
+    pub(crate) fn len(&self) -> usize {
+        self.buffer.len() - self.skip
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub(crate) fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+    pub(crate) fn fill_level(&self) -> FillLevel {
+        FillLevel::new(self.len(), self.capacity())
+    }
+    pub(crate) fn pushed(&self) -> u64 {
+        self.buffer.pushed()
+    }
+    pub(crate) fn cleared(&self) -> u64 {
+        self.buffer.cleared()
+    }
+    pub(crate) fn state(&self) -> State {
+        State {
+            len: self.len(),
+            capacity: self.capacity(),
+            fill_level: self.fill_level(),
+            pushed: self.pushed(),
+        }
+    }
+    pub(crate) fn as_slices(&self) -> (&[T], &[T]) {
+        self.buffer.range(self.skip..).unwrap()
+    }
+    pub(crate) fn time_slices(&self) -> Option<(&[Duration], &[Duration])> {
+        let times = self.buffer.time_slices()?;
+        Some(buffer::sub_slices(times, self.skip..self.buffer.len()))
+    }
+    pub(crate) fn get(&self, index: usize) -> Option<&T> {
+        self.buffer.get(index.checked_add(self.skip)?)
+    }
+    pub(crate) fn seq(&self, index: usize) -> Option<u64> {
+        self.buffer.seq(index.checked_add(self.skip)?)
+    }
+    // logical index of the first element with a sequence number of at least `seq`
+    pub(crate) fn position(&self, seq: u64) -> usize {
+        self.buffer.position(seq).max(self.skip) - self.skip
+    }
+    pub(crate) fn range(&self, range: impl RangeBounds<usize>) -> Option<(&[T], &[T])> {
+        let range = buffer::bounds(range, self.len())?;
+        self.buffer
+            .range(range.start + self.skip..range.end + self.skip)
+    }
+}
+impl<T: Clone, L: RawLock> Live<'_, T, L> {
+    pub(crate) fn head(&self) -> Option<T> {
+        let (older, newer) = self.as_slices();
+        newer.last().or(older.last()).cloned()
+    }
+    pub(crate) fn snapshot(&self) -> Vec<T> {
+        let mut out = Vec::with_capacity(self.len());
+        self.snapshot_into(&mut out);
+        out
+    }
+    pub(crate) fn snapshot_into(&self, out: &mut Vec<T>) {
+        let (older, newer) = self.as_slices();
+        out.clear();
+        out.extend_from_slice(older);
+        out.extend_from_slice(newer);
+    }
+}
+
+impl<T, L: RawLock> Builder<T, L> {
+    /// Drops values once they are older than `max_age`, in addition to the capacity limit.
+    ///
+    /// Readers stop seeing values as soon as they expire. They are removed, and passed to
+    /// `on_evict`, by the next push, pop, `resize`, `retain` or `Buffer::expire`. Without the
+    /// `std` feature this needs a `clock`.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+    /// Timestamps every pushed value with `clock`, for `max_age` and `range_by_time`. Defaults
+    /// to a `SystemClock` with `max_age`.
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+}
+
+impl<T, L: RawLock> Buffer<T, L> {
+    /// Drops every value older than `max_age` and returns how many there were.
+    pub fn expire(&self) -> usize {
+        self.shared.expire()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_age() {
+        let clock = ManualClock::new();
+        let buffer = Buffer::builder(4)
+            .max_age(Duration::from_secs(10))
+            .clock(clock.clone())
+            .build();
+        let reader = buffer.reader();
+
+        buffer.push(1);
+        clock.advance(Duration::from_secs(5));
+        buffer.push_slice(&[2, 3]);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(reader.snapshot(), vec![1, 2, 3]);
+
+        // hidden from readers, but only removed by the next push
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(reader.len(), 2);
+        assert_eq!(reader.snapshot(), vec![2, 3]);
+
+        // the capacity still applies
+        buffer.push_slice(&[4, 5, 6]);
+        assert_eq!(buffer.snapshot(), vec![3, 4, 5, 6]);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(buffer.expire(), 1);
+        assert_eq!(buffer.expire(), 0);
+        clock.advance(Duration::from_secs(60));
+        buffer.push(7);
+        assert_eq!(buffer.snapshot(), vec![7]);
+        assert_eq!(buffer.stats().expired, 5);
+    }
+
+    #[test]
+    fn readers() {
+        let clock = ManualClock::new();
+        let evicted = Buffer::new(8);
+        let buffer = Buffer::builder(4)
+            .max_age(Duration::from_secs(1))
+            .clock(clock.clone())
+            .on_evict({
+                let evicted = evicted.writer();
+                move |value| evicted.push(value)
+            })
+            .build();
+        let mut subscriber = buffer.subscribe();
+        buffer.push_slice(&[1, 2]);
+        clock.advance(Duration::from_secs(1));
+        buffer.push(3);
+
+        clock.advance(Duration::from_millis(1));
+        let guard = buffer.read();
+        // only takes the read lock again
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(guard.iter().copied().collect::<Vec<_>>(), vec![3]);
+        assert_eq!(guard.oldest_time(), Some(Duration::from_secs(1)));
+        drop(guard);
+        assert_eq!(buffer.get(0), Some(3));
+        assert_eq!(buffer.read_since(Cursor::default()).0, vec![3]);
+        assert_eq!(subscriber.try_recv(), Err(RecvError::Lagged(2)));
+        assert_eq!(subscriber.try_recv(), Ok(3));
+        assert!(evicted.is_empty());
+        assert_eq!(buffer.stats().expired, 0);
+
+        assert_eq!(buffer.write_guard().len(), 1);
+        assert_eq!(buffer.pop_front(), Some(3));
+        assert_eq!(evicted.snapshot(), vec![1, 2]);
+    }
+
+    #[test]
+    fn evict() {
+        let clock = ManualClock::new();
+        let evicted = Buffer::new(8);
+        let buffer = Buffer::builder(2)
+            .overflow(Overflow::RejectNew)
+            .max_age(Duration::from_secs(1))
+            .clock(clock.clone())
+            .on_evict({
+                let evicted = evicted.writer();
+                move |value| evicted.push(value)
+            })
+            .build();
+
+        assert_eq!(buffer.push_slice(&[1, 2, 3]), 2);
+        assert_eq!(buffer.try_push(4), Err(PushError::Full(4)));
+        // expiry makes room before the policy is applied
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(buffer.try_push(4), Ok(()));
+        assert_eq!(buffer.snapshot(), vec![4]);
+        assert_eq!(evicted.snapshot(), vec![1, 2]);
+    }
+}
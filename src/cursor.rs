@@ -0,0 +1,123 @@
+use super::*;
+use crate::clock::Live;
+
+/// A read position in the sequence of values pushed into a buffer.
+///
+/// Every pushed value gets the next sequence number, starting at 0. The default cursor
+/// starts before the first value ever pushed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Cursor {
+    seq: u64,
+    lost: u64,
+}
+
+impl Cursor {
+    /// Sequence number of the next value this cursor reads.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+    /// Values the read returning this cursor skipped because they had already been
+    /// overwritten or removed.
+    pub fn lost(&self) -> u64 {
+        self.lost
+    }
+}
+
+impl<T, L: RawLock> Live<'_, T, L> {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            seq: self.pushed(),
+            lost: 0,
+        }
+    }
+}
+impl<T: Clone, L: RawLock> Live<'_, T, L> {
+    fn read_since(&self, cursor: Cursor) -> (Vec<T>, Cursor) {
+        let (older, newer) = self.as_slices();
+        let values: Vec<T> = older
+            .iter()
+            .chain(newer)
+            .skip(self.position(cursor.seq))
+            .cloned()
+            .collect();
+        let pushed = self.pushed();
+        let lost = pushed
+            .saturating_sub(cursor.seq)
+            .saturating_sub(values.len() as u64);
+        let cursor = Cursor {
+            seq: pushed.max(cursor.seq),
+            lost,
+        };
+        (values, cursor)
+    }
+}
+
+impl<T, L: RawLock> Buffer<T, L> {
+    /// A cursor after the newest value, so the next read returns only values pushed later.
+    pub fn cursor(&self) -> Cursor {
+        self.shared.read().cursor()
+    }
+}
+impl<T: Clone, L: RawLock> Buffer<T, L> {
+    /// Values pushed at or after `cursor` that are still buffered, oldest first, and the
+    /// cursor to continue from.
+    pub fn read_since(&self, cursor: Cursor) -> (Vec<T>, Cursor) {
+        self.shared.read().read_since(cursor)
+    }
+}
+
+impl<T, L: RawLock> Reader<T, L> {
+    pub fn cursor(&self) -> Cursor {
+        self.shared.read().cursor()
+    }
+}
+impl<T: Clone, L: RawLock> Reader<T, L> {
+    pub fn read_since(&self, cursor: Cursor) -> (Vec<T>, Cursor) {
+        self.shared.read().read_since(cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_since() {
+        let buffer = Buffer::new(3);
+        buffer.push_slice(&[1, 2]);
+
+        let (values, cursor) = buffer.read_since(Cursor::default());
+        assert_eq!(values, vec![1, 2]);
+        assert_eq!((cursor.seq(), cursor.lost()), (2, 0));
+
+        let (values, cursor) = buffer.read_since(cursor);
+        assert_eq!(values, vec![]);
+        assert_eq!((cursor.seq(), cursor.lost()), (2, 0));
+
+        buffer.push_slice(&[3, 4, 5, 6]);
+        let (values, cursor) = buffer.read_since(cursor);
+        assert_eq!(values, vec![4, 5, 6]);
+        assert_eq!((cursor.seq(), cursor.lost()), (6, 1));
+        assert_eq!(buffer.cursor(), Cursor { seq: 6, lost: 0 });
+    }
+
+    #[test]
+    fn gaps() {
+        let buffer = Buffer::new(4);
+        let cursor = buffer.cursor();
+        buffer.push_slice(&[1, 2, 3]);
+        assert_eq!(buffer.pop_back(), Some(3));
+        buffer.push(4);
+
+        let (values, cursor) = buffer.read_since(cursor);
+        assert_eq!(values, vec![1, 2, 4]);
+        assert_eq!((cursor.seq(), cursor.lost()), (4, 1));
+
+        buffer.push(5);
+        buffer.clear();
+        buffer.push(6);
+        let (values, cursor) = buffer.read_since(cursor);
+        assert_eq!(values, vec![6]);
+        assert_eq!((cursor.seq(), cursor.lost()), (6, 1));
+    }
+}
@@ -0,0 +1,130 @@
+use super::*;
+
+type OnPush<T> = Box<dyn Fn(&T) + Send + Sync>;
+type OnEvict<T> = Box<dyn Fn(T) + Send + Sync>;
+type OnClear = Box<dyn Fn() + Send + Sync>;
+
+pub(crate) struct Hooks<T> {
+    on_push: Option<OnPush<T>>,
+    on_evict: Option<OnEvict<T>>,
+    on_clear: Option<OnClear>,
+}
+
+impl<T> Default for Hooks<T> {
+    fn default() -> Self {
+        Self {
+            on_push: None,
+            on_evict: None,
+            on_clear: None,
+        }
+    }
+}
+
+impl<T> Hooks<T> {
+    // under the write lock
+    pub(crate) fn push(&self, value: &T) {
+        if let Some(on_push) = &self.on_push {
+            on_push(value);
+        }
+    }
+    #[cfg(feature = "std")]
+    pub(crate) fn pushes(&self) -> bool {
+        self.on_push.is_some()
+    }
+    pub(crate) fn evicts(&self) -> bool {
+        self.on_evict.is_some()
+    }
+    // only once the write lock is released
+    pub(crate) fn evict(&self, evicted: impl IntoIterator<Item = T>) {
+        if let Some(on_evict) = &self.on_evict {
+            evicted.into_iter().for_each(on_evict);
+        }
+    }
+    // only once the write lock is released
+    pub(crate) fn clear(&self) {
+        if let Some(on_clear) = &self.on_clear {
+            on_clear();
+        }
+    }
+}
+
+/// Hooks observe values as they move through the buffer.
+///
+/// `on_push` runs under the write lock, so it must not call back into the same buffer or it
+/// deadlocks. `on_evict` and `on_clear` run after the lock is released and may use the buffer
+/// freely; they can race with other pushes.
+impl<T, L: RawLock> Builder<T, L> {
+    /// Called with every value about to be stored, including values a zero capacity buffer
+    /// drops right away. Not called for values merged by `push_or_merge`.
+    pub fn on_push(mut self, on_push: impl Fn(&T) + Send + Sync + 'static) -> Self {
+        self.hooks.on_push = Some(Box::new(on_push));
+        self
+    }
+    /// Called with every value a push overwrites to make room, and every value dropped for
+    /// exceeding `max_age`. Values handed back by `pop_*`, `drain` or `resize`, or dropped by
+    /// `clear`, are not evicted.
+    pub fn on_evict(mut self, on_evict: impl Fn(T) + Send + Sync + 'static) -> Self {
+        self.hooks.on_evict = Some(Box::new(on_evict));
+        self
+    }
+    pub fn on_clear(mut self, on_clear: impl Fn() + Send + Sync + 'static) -> Self {
+        self.hooks.on_clear = Some(Box::new(on_clear));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evict() {
+        let spill = Buffer::new(8);
+        let pushed = Buffer::new(8);
+        let buffer = Buffer::builder(2)
+            .on_push({
+                let pushed = pushed.writer();
+                move |value: &u32| pushed.push(*value)
+            })
+            .on_evict({
+                let spill = spill.writer();
+                move |value| spill.push(value)
+            })
+            .build();
+
+        buffer.push_slice(&[1, 2, 3, 4]);
+        buffer.push(5);
+        assert_eq!(buffer.push_or_merge(6, |head, _| *head == 5), Ok(true));
+        assert_eq!(spill.snapshot(), vec![1, 2, 3]);
+        assert_eq!(pushed.snapshot(), vec![1, 2, 3, 4, 5]);
+
+        assert_eq!(buffer.resize(1), vec![4]);
+        buffer.pop_front();
+        buffer.clear();
+        assert_eq!(spill.snapshot(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn clear() {
+        let cleared = Arc::new(AtomicUsize::new(0));
+        let evicted = Buffer::new(2);
+        let sink = Buffer::builder(0)
+            .on_evict({
+                let evicted = evicted.writer();
+                move |value| evicted.push(value)
+            })
+            .on_clear({
+                let cleared = cleared.clone();
+                move || {
+                    cleared.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .build();
+
+        sink.push(1);
+        assert_eq!(evicted.snapshot(), vec![1]);
+        sink.clear();
+        sink.clear();
+        assert_eq!(cleared.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,237 @@
+use core::ops::RangeBounds;
+
+use super::*;
+use crate::clock::Live;
+use crate::lock::ExclusiveGuard;
+
+impl<T, L: RawLock> Live<'_, T, L> {
+    fn index_from_newest(&self, index: usize) -> Option<usize> {
+        self.len().checked_sub(1)?.checked_sub(index)
+    }
+    fn first_n(&self, n: usize) -> (&[T], &[T]) {
+        self.range(..n.min(self.len())).unwrap()
+    }
+    fn last_n(&self, n: usize) -> (&[T], &[T]) {
+        self.range(self.len() - n.min(self.len())..).unwrap()
+    }
+}
+
+fn to_vec<T: Clone>((older, newer): (&[T], &[T])) -> Vec<T> {
+    let mut out = Vec::with_capacity(older.len() + newer.len());
+    out.extend_from_slice(older);
+    out.extend_from_slice(newer);
+    out
+}
+
+impl<T, L: RawLock> ReadGuard<'_, T, L> {
+    /// The element at `index`, 0 being the oldest.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.buffer.get(index)
+    }
+    /// The element at `index`, 0 being the newest.
+    pub fn get_from_newest(&self, index: usize) -> Option<&T> {
+        self.get(self.buffer.index_from_newest(index)?)
+    }
+    /// Iterates over a range of indices, 0 being the oldest. `None` if it is out of bounds.
+    pub fn range(&self, range: impl RangeBounds<usize>) -> Option<Iter<'_, T>> {
+        let (older, newer) = self.buffer.range(range)?;
+        Some(Iter::new(older, newer))
+    }
+}
+
+/// Mutable access to the buffer contents in place. Holds the write lock until dropped.
+///
+/// Like readers, it does not see values that have expired under `max_age`.
+pub struct WriteGuard<'a, T, L: RawLock = DefaultLock> {
+    buffer: ExclusiveGuard<'a, L, buffer::Buffer<T>>,
+    // expired values at the front of `buffer`
+    skip: usize,
+}
+
+impl<T, L: RawLock> WriteGuard<'_, T, L> {
+    fn index_from_newest(&self, index: usize) -> Option<usize> {
+        self.len().checked_sub(1)?.checked_sub(index)
+    }
+    pub fn len(&self) -> usize {
+        self.buffer.len() - self.skip
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.buffer.get(index.checked_add(self.skip)?)
+    }
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.buffer.get_mut(index.checked_add(self.skip)?)
+    }
+    pub fn get_from_newest(&self, index: usize) -> Option<&T> {
+        self.get(self.index_from_newest(index)?)
+    }
+    pub fn get_from_newest_mut(&mut self, index: usize) -> Option<&mut T> {
+        let index = self.index_from_newest(index)?;
+        self.get_mut(index)
+    }
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let skip = self.skip;
+        let (older, newer) = self.buffer.as_mut_slices();
+        let split = older.len().min(skip);
+        (&mut older[split..], &mut newer[skip - split..])
+    }
+}
+
+impl<T, L: RawLock> Buffer<T, L> {
+    pub fn write_guard(&self) -> WriteGuard<'_, T, L> {
+        let buffer = self.shared.buffer.write();
+        let skip = self.shared.expired(&buffer);
+        WriteGuard { buffer, skip }
+    }
+    /// Calls `f` on the element at `index`, 0 being the oldest.
+    pub fn update<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.write_guard().get_mut(index).map(f)
+    }
+    /// Calls `f` on the element at `index`, 0 being the newest.
+    pub fn update_from_newest<R>(&self, index: usize, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.write_guard().get_from_newest_mut(index).map(f)
+    }
+    /// Calls `f` on the newest element.
+    pub fn modify_head<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.write_guard().get_from_newest_mut(0).map(f)
+    }
+    /// Removes every element for which `f` returns `false`, keeping the rest in order.
+    pub fn retain(&self, f: impl FnMut(&T) -> bool) {
+        self.shared.update_live(|buffer| buffer.retain(f))
+    }
+}
+impl<T: Clone, L: RawLock> Buffer<T, L> {
+    /// The element at `index`, 0 being the oldest.
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.shared.read().get(index).cloned()
+    }
+    /// The element at `index`, 0 being the newest.
+    pub fn get_from_newest(&self, index: usize) -> Option<T> {
+        self.read().get_from_newest(index).cloned()
+    }
+    /// Up to `n` of the oldest elements, oldest first.
+    pub fn first_n(&self, n: usize) -> Vec<T> {
+        to_vec(self.shared.read().first_n(n))
+    }
+    /// Up to `n` of the newest elements, oldest first.
+    pub fn last_n(&self, n: usize) -> Vec<T> {
+        to_vec(self.shared.read().last_n(n))
+    }
+    /// The elements in a range of indices, 0 being the oldest. `None` if it is out of bounds.
+    pub fn range(&self, range: impl RangeBounds<usize>) -> Option<Vec<T>> {
+        self.shared.read().range(range).map(to_vec)
+    }
+}
+
+impl<T: Clone, L: RawLock> Reader<T, L> {
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.shared.read().get(index).cloned()
+    }
+    pub fn get_from_newest(&self, index: usize) -> Option<T> {
+        self.read().get_from_newest(index).cloned()
+    }
+    pub fn first_n(&self, n: usize) -> Vec<T> {
+        to_vec(self.shared.read().first_n(n))
+    }
+    pub fn last_n(&self, n: usize) -> Vec<T> {
+        to_vec(self.shared.read().last_n(n))
+    }
+    pub fn range(&self, range: impl RangeBounds<usize>) -> Option<Vec<T>> {
+        self.shared.read().range(range).map(to_vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::*;
+
+    #[test]
+    fn get() {
+        let buffer = Buffer::new(4);
+        assert_eq!(buffer.get(0), None);
+        assert_eq!(buffer.get_from_newest(0), None);
+
+        buffer.push_slice(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(buffer.get(0), Some(3));
+        assert_eq!(buffer.get(3), Some(6));
+        assert_eq!(buffer.get(4), None);
+        assert_eq!(buffer.get_from_newest(0), Some(6));
+        assert_eq!(buffer.get_from_newest(3), Some(3));
+        assert_eq!(buffer.get_from_newest(4), None);
+        assert_eq!(buffer.get_from_newest(usize::MAX), None);
+
+        let guard = buffer.read();
+        assert_eq!(guard.get(1), Some(&4));
+        assert_eq!(guard.get_from_newest(1), Some(&5));
+    }
+
+    #[test]
+    fn ranges() {
+        let buffer = Buffer::new(4);
+        buffer.push_slice(&[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(buffer.first_n(2), vec![3, 4]);
+        assert_eq!(buffer.last_n(3), vec![4, 5, 6]);
+        assert_eq!(buffer.last_n(10), vec![3, 4, 5, 6]);
+        assert_eq!(buffer.first_n(0), vec![]);
+
+        assert_eq!(buffer.range(1..3), Some(vec![4, 5]));
+        assert_eq!(buffer.range(1..=3), Some(vec![4, 5, 6]));
+        assert_eq!(buffer.range(..), Some(vec![3, 4, 5, 6]));
+        assert_eq!(buffer.range(2..2), Some(vec![]));
+        assert_eq!(buffer.range(2..5), None);
+        assert_eq!(buffer.range((Bound::Included(3), Bound::Excluded(2))), None);
+
+        let guard = buffer.read();
+        let rev: Vec<_> = guard.range(1..).unwrap().rev().copied().collect();
+        assert_eq!(rev, vec![6, 5, 4]);
+    }
+
+    #[test]
+    fn update() {
+        let buffer = Buffer::new(3);
+        buffer.push_slice(&[1, 2, 3, 4]);
+
+        assert_eq!(buffer.update(0, |value| *value *= 10), Some(()));
+        assert_eq!(buffer.update_from_newest(0, |value| *value + 1), Some(5));
+        assert_eq!(buffer.update(3, |value| *value *= 10), None);
+
+        let mut guard = buffer.write_guard();
+        *guard.get_from_newest_mut(1).unwrap() = 0;
+        drop(guard);
+        assert_eq!(buffer.snapshot(), vec![20, 0, 4]);
+    }
+
+    #[test]
+    fn modify_head() {
+        let buffer = Buffer::new(3);
+        assert_eq!(buffer.modify_head(|head: &mut u32| *head += 1), None);
+        buffer.push_slice(&[1, 2]);
+        assert_eq!(buffer.modify_head(|head| *head += 10), Some(()));
+        assert_eq!(buffer.snapshot(), vec![1, 12]);
+    }
+
+    #[test]
+    fn retain() {
+        let buffer = Buffer::new(5);
+        buffer.push_slice(&[1, 2, 3, 4, 5, 6, 7]);
+        let cursor = buffer.cursor();
+
+        buffer.retain(|value| value % 2 == 1);
+        assert_eq!(buffer.snapshot(), vec![3, 5, 7]);
+        assert_eq!(buffer.fill_level(), FillLevel::Partial);
+
+        buffer.push_slice(&[8, 9, 10]);
+        assert_eq!(buffer.snapshot(), vec![5, 7, 8, 9, 10]);
+        let (values, cursor) = buffer.read_since(cursor);
+        assert_eq!(values, vec![8, 9, 10]);
+        assert_eq!(cursor.lost(), 0);
+
+        buffer.retain(|_| false);
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,239 @@
+use std::io::{self, BufRead, Read, Write};
+
+use super::*;
+
+// size of the chunk `ByteReader::fill_buf` copies out of the buffer at once
+const CHUNK: usize = 8 * 1024;
+
+impl<L: RawLock> Shared<u8, L> {
+    // copies in bulk, unless a hook wants to see every byte or a full buffer has to block
+    fn write(&self, bytes: &[u8]) -> usize {
+        let per_byte = self.hooks.pushes() || self.hooks.evicts();
+        if per_byte || matches!(self.overflow, Overflow::Block { .. }) {
+            return self.push_slice(bytes);
+        }
+        // stays empty without `on_evict`
+        let mut expired = Vec::new();
+        self.update(|buffer| {
+            self.expire_locked(buffer, &mut expired);
+            let accepted = match self.overflow {
+                Overflow::RejectNew => bytes.len().min(buffer.capacity() - buffer.len()),
+                _ => bytes.len(),
+            };
+            let overwritten = buffer.push_copied(&bytes[..accepted], self.now());
+            let overwritten = if buffer.capacity() > 0 {
+                overwritten
+            } else {
+                0
+            };
+            buffer.stats.push_n(accepted, overwritten);
+            accepted
+        })
+    }
+}
+
+/// Appends bytes like `push_slice`, overwriting the oldest ones once full, but with at most two
+/// bulk copies. Bytes are pushed one by one instead if there is an `on_push` or `on_evict` hook
+/// or the overflow policy is `Overflow::Block`.
+///
+/// A write only comes up short under `Overflow::RejectNew` or after an `Overflow::Block`
+/// timeout, so `write_all` fails with `ErrorKind::WriteZero` once the buffer is full.
+impl<L: RawLock> Write for Buffer<u8, L> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(self.shared.write(buf))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<L: RawLock> Write for Writer<u8, L> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the bytes pushed into a `Buffer<u8>` as a stream, starting at the oldest byte still
+/// buffered, without removing them.
+///
+/// Reads block until there are new bytes, and return 0 once every writer is gone and all bytes
+/// have been read. Bytes that are overwritten before they are read are skipped and counted by
+/// `lost`.
+pub struct ByteReader<L: RawLock = DefaultLock> {
+    reader: Reader<u8, L>,
+    // sequence number of the next byte to read
+    next: u64,
+    lost: u64,
+    // bytes copied out by `fill_buf`, of which the first `consumed` have been read
+    chunk: Vec<u8>,
+    consumed: usize,
+}
+
+impl<L: RawLock> ByteReader<L> {
+    fn new(reader: Reader<u8, L>) -> Self {
+        let buffer = reader.shared.read();
+        let next = buffer.seq(0).unwrap_or(buffer.pushed());
+        drop(buffer);
+        Self {
+            reader,
+            next,
+            lost: 0,
+            chunk: Vec::new(),
+            consumed: 0,
+        }
+    }
+    /// Total number of bytes skipped because they were overwritten or removed before being read.
+    pub fn lost(&self) -> u64 {
+        self.lost
+    }
+    // blocks until `out` has been filled with at least one byte, or returns 0 once closed
+    fn fetch(&mut self, out: &mut [u8]) -> usize {
+        if out.is_empty() {
+            return 0;
+        }
+        let ByteReader {
+            reader, next, lost, ..
+        } = self;
+        let fetched = reader
+            .shared
+            .wait_for(|| fetch(reader, next, lost, out), None);
+        fetched.unwrap()
+    }
+}
+
+// copies the buffered bytes from `next` on into `out`; `None` means there is nothing to read yet
+fn fetch<L: RawLock>(
+    reader: &Reader<u8, L>,
+    next: &mut u64,
+    lost: &mut u64,
+    out: &mut [u8],
+) -> Option<usize> {
+    // checked first, so bytes written by the last writer are still read
+    let closed = reader.is_closed();
+    let buffer = reader.shared.read();
+    let index = buffer.position(*next);
+    if index == buffer.len() {
+        *lost += buffer.pushed().saturating_sub(*next);
+        *next = buffer.pushed().max(*next);
+        return closed.then_some(0);
+    }
+    let (older, newer) = buffer.range(index..).unwrap();
+    let split = older.len().min(out.len());
+    let len = (older.len() + newer.len()).min(out.len());
+    out[..split].copy_from_slice(&older[..split]);
+    out[split..len].copy_from_slice(&newer[..len - split]);
+    // sequence numbers can have gaps where bytes were removed
+    let end = buffer.seq(index + len - 1).unwrap() + 1;
+    *lost += end - *next - len as u64;
+    *next = end;
+    Some(len)
+}
+
+impl<L: RawLock> Read for ByteReader<L> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.consumed == self.chunk.len() {
+            return Ok(self.fetch(buf));
+        }
+        let len = {
+            let mut available = self.fill_buf()?;
+            available.read(buf)?
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<L: RawLock> BufRead for ByteReader<L> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.consumed == self.chunk.len() {
+            let mut chunk = mem::take(&mut self.chunk);
+            chunk.resize(CHUNK, 0);
+            let len = self.fetch(&mut chunk);
+            chunk.truncate(len);
+            self.chunk = chunk;
+            self.consumed = 0;
+        }
+        Ok(&self.chunk[self.consumed..])
+    }
+    fn consume(&mut self, amt: usize) {
+        self.consumed = (self.consumed + amt).min(self.chunk.len());
+    }
+}
+
+impl<L: RawLock> Buffer<u8, L> {
+    pub fn byte_reader(&self) -> ByteReader<L> {
+        ByteReader::new(self.reader())
+    }
+}
+
+impl<L: RawLock> Reader<u8, L> {
+    pub fn byte_reader(&self) -> ByteReader<L> {
+        ByteReader::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn write() {
+        let mut buffer = Buffer::new(8);
+        buffer.write_all(b"hello").unwrap();
+        write!(buffer, ", {}!", 42).unwrap();
+        assert_eq!(buffer.snapshot(), b"llo, 42!");
+        buffer.write_all(b"0123456789abc").unwrap();
+        assert_eq!(buffer.snapshot(), b"56789abc");
+
+        let stats = buffer.stats();
+        assert_eq!((stats.pushes, stats.overwritten), (23, 15));
+
+        let mut rejecting = Buffer::builder(4).overflow(Overflow::RejectNew).build();
+        assert_eq!(rejecting.write(b"abcdef").unwrap(), 4);
+        let error = rejecting.write_all(b"g").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn hooks() {
+        let evicted = Buffer::new(8);
+        let mut buffer = Buffer::builder(4)
+            .on_evict({
+                let evicted = evicted.writer();
+                move |byte| evicted.push(byte)
+            })
+            .build();
+        buffer.write_all(b"abcdef").unwrap();
+        assert_eq!(buffer.snapshot(), b"cdef");
+        assert_eq!(evicted.snapshot(), b"ab");
+    }
+
+    #[test]
+    fn read() {
+        let buffer = Buffer::new(4);
+        let mut writer = buffer.writer();
+        writer.write_all(b"abcdef").unwrap();
+        let mut reader = buffer.byte_reader();
+        drop(buffer);
+
+        let mut bytes = [0; 3];
+        assert_eq!(reader.read(&mut bytes).unwrap(), 3);
+        assert_eq!(&bytes, b"cde");
+        writer.write_all(b"ghijk").unwrap();
+        assert_eq!(reader.fill_buf().unwrap(), b"hijk");
+        assert_eq!(reader.lost(), 2);
+        reader.consume(1);
+
+        let producer = thread::spawn(move || writer.write_all(b"\nlm\n"));
+        let mut lines = String::new();
+        reader.read_line(&mut lines).unwrap();
+        producer.join().unwrap().unwrap();
+        reader.read_to_string(&mut lines).unwrap();
+        assert_eq!(lines, "ijk\nlm\n");
+    }
+}
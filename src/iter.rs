@@ -0,0 +1,194 @@
+use core::iter::FusedIterator;
+use core::slice;
+
+#[cfg(feature = "alloc")]
+use super::*;
+#[cfg(feature = "alloc")]
+use crate::clock::Live;
+
+/// Borrowed access to the buffer contents. Holds the read lock until dropped, so writers
+/// wait for it.
+#[cfg(feature = "alloc")]
+pub struct ReadGuard<'a, T, L: RawLock = DefaultLock> {
+    pub(crate) buffer: Live<'a, T, L>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> ReadGuard<'_, T, L> {
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+    /// The contents as (older, newer) halves; their concatenation is oldest to newest.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.buffer.as_slices()
+    }
+    pub fn head(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+    /// Iterates from oldest to newest, or newest to oldest with `rev()`.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (older, newer) = self.as_slices();
+        Iter::new(older, newer)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, L: RawLock> IntoIterator for &'a ReadGuard<'_, T, L> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T> {
+    older: slice::Iter<'a, T>,
+    newer: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(crate) fn new(older: &'a [T], newer: &'a [T]) -> Self {
+        Self {
+            older: older.iter(),
+            newer: newer.iter(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.older.next().or_else(|| self.newer.next())
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.newer.next_back().or_else(|| self.older.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    fn len(&self) -> usize {
+        self.older.len() + self.newer.len()
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            older: self.older.clone(),
+            newer: self.newer.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Buffer<T, L> {
+    pub fn read(&self) -> ReadGuard<'_, T, L> {
+        ReadGuard {
+            buffer: self.shared.read(),
+        }
+    }
+    /// Calls `f` with the (older, newer) halves of the contents under the read lock.
+    pub fn with_slices<R>(&self, f: impl FnOnce(&[T], &[T]) -> R) -> R {
+        let buffer = self.shared.read();
+        let (older, newer) = buffer.as_slices();
+        f(older, newer)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Clone, L: RawLock> Buffer<T, L> {
+    /// Like `snapshot`, but reuses the allocation of `out`.
+    pub fn snapshot_into(&self, out: &mut Vec<T>) {
+        self.shared.read().snapshot_into(out)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Reader<T, L> {
+    pub fn read(&self) -> ReadGuard<'_, T, L> {
+        ReadGuard {
+            buffer: self.shared.read(),
+        }
+    }
+    pub fn with_slices<R>(&self, f: impl FnOnce(&[T], &[T]) -> R) -> R {
+        let buffer = self.shared.read();
+        let (older, newer) = buffer.as_slices();
+        f(older, newer)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Clone, L: RawLock> Reader<T, L> {
+    pub fn snapshot_into(&self, out: &mut Vec<T>) {
+        self.shared.read().snapshot_into(out)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    struct Frame(u32);
+
+    #[test]
+    fn iter() {
+        let buffer = Buffer::new(3);
+        for index in 0..5 {
+            buffer.push(Frame(index));
+        }
+
+        let guard = buffer.read();
+        assert_eq!(guard.len(), 3);
+        assert_eq!(guard.head().map(|frame| frame.0), Some(4));
+        let forward: Vec<_> = guard.iter().map(|frame| frame.0).collect();
+        assert_eq!(forward, vec![2, 3, 4]);
+        let backward: Vec<_> = guard.iter().rev().map(|frame| frame.0).collect();
+        assert_eq!(backward, vec![4, 3, 2]);
+
+        let mut iter = guard.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back().map(|frame| frame.0), Some(4));
+        assert_eq!(iter.next().map(|frame| frame.0), Some(2));
+        assert_eq!(iter.len(), 1);
+    }
+
+    #[test]
+    fn with_slices() {
+        let buffer = Buffer::new(3);
+        for index in 0..4 {
+            buffer.push(Frame(index));
+        }
+        let total = buffer.with_slices(|older, newer| {
+            assert_eq!(older.len() + newer.len(), 3);
+            older.iter().chain(newer).map(|frame| frame.0).sum::<u32>()
+        });
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    fn snapshot_into() {
+        let buffer = Buffer::new(4);
+        let mut out = Vec::with_capacity(4);
+        buffer.push_slice(&[1, 2, 3]);
+        buffer.snapshot_into(&mut out);
+        assert_eq!(out, vec![1, 2, 3]);
+
+        let ptr = out.as_ptr();
+        buffer.push_slice(&[4, 5]);
+        buffer.snapshot_into(&mut out);
+        assert_eq!(out, vec![2, 3, 4, 5]);
+        assert_eq!(out.as_ptr(), ptr);
+    }
+}
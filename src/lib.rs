@@ -1,77 +1,547 @@
-use std::sync::Arc;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-use parking_lot::RwLock;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+// `Arc` and the shared buffers' counters rely on atomic read-modify-write
+#[cfg(all(feature = "alloc", not(target_has_atomic = "ptr")))]
+compile_error!("the `alloc` feature needs a target with pointer-sized atomic compare-and-swap");
+
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::marker::PhantomData;
+#[cfg(feature = "alloc")]
+use core::mem;
+#[cfg(feature = "alloc")]
+use core::sync::atomic::{fence, AtomicUsize, Ordering};
+#[cfg(feature = "alloc")]
+use core::task::Waker;
+#[cfg(feature = "alloc")]
+use core::time::Duration;
+
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, sync::Arc, vec, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use hooks::Hooks;
+use lock::Lock;
+
+pub use array::{ArrayBuffer, SyncArrayBuffer};
+#[cfg(feature = "alloc")]
+pub use broadcast::{RecvError, Subscriber};
+#[cfg(feature = "alloc")]
+pub use clock::Clock;
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+pub use clock::ManualClock;
+#[cfg(feature = "std")]
+pub use clock::SystemClock;
+#[cfg(feature = "alloc")]
+pub use cursor::Cursor;
+#[cfg(feature = "alloc")]
+pub use index::WriteGuard;
+#[cfg(feature = "std")]
+pub use io::ByteReader;
+pub use iter::Iter;
+#[cfg(feature = "alloc")]
+pub use iter::ReadGuard;
+pub use lock::RawLock;
+#[cfg(target_has_atomic = "ptr")]
+pub use lock::{DefaultLock, SpinLock};
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+pub use lossy::LossyBuffer;
+#[cfg(feature = "alloc")]
+pub use numeric::{NumericBuffer, NumericBuilder, Summary};
+pub use overflow::{Overflow, PushError};
+#[cfg(feature = "alloc")]
+pub use spsc::{Consumer, Producer, SpscBuffer};
+#[cfg(feature = "alloc")]
+pub use stats::Stats;
+#[cfg(feature = "alloc")]
+pub use stream::{Event, NextEvent, NextPush, PushStream};
+#[cfg(feature = "alloc")]
+pub use timestamps::TimedIter;
+#[cfg(feature = "std")]
+pub use timestamps::WallClock;
+#[cfg(feature = "alloc")]
+pub use wait::State;
+
+mod array;
+#[cfg(feature = "alloc")]
+mod broadcast;
+#[cfg(feature = "alloc")]
+mod clock;
+#[cfg(feature = "alloc")]
+mod cursor;
+#[cfg(feature = "alloc")]
+mod hooks;
+#[cfg(feature = "alloc")]
+mod index;
+#[cfg(feature = "std")]
+mod io;
+mod iter;
+mod lock;
+// claims sequence numbers with 64-bit atomics
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+mod lossy;
+#[cfg(feature = "alloc")]
+mod numeric;
+mod overflow;
+#[cfg(feature = "alloc")]
+mod quantile;
+#[cfg(feature = "alloc")]
+mod rate;
+#[cfg(feature = "alloc")]
+mod spsc;
+#[cfg(feature = "alloc")]
+mod stats;
+#[cfg(feature = "alloc")]
+mod stream;
+#[cfg(feature = "alloc")]
+mod timestamps;
+#[cfg(feature = "alloc")]
+mod wait;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FillLevel {
     Empty,
     Partial,
     Full,
 }
 
-pub struct Buffer<T> {
-    buffer: Arc<RwLock<buffer::Buffer<T>>>,
+impl FillLevel {
+    pub(crate) fn new(len: usize, capacity: usize) -> Self {
+        match len {
+            0 => FillLevel::Empty,
+            x if x == capacity => FillLevel::Full,
+            _ => FillLevel::Partial,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZeroCapacity;
+
+impl fmt::Display for ZeroCapacity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("buffer capacity must be greater than zero")
+    }
+}
+
+impl core::error::Error for ZeroCapacity {}
+
+#[cfg(feature = "alloc")]
+struct Shared<T, L: RawLock> {
+    buffer: Lock<L, buffer::Buffer<T>>,
+    overflow: Overflow,
+    hooks: Hooks<T>,
+    clock: Option<Box<dyn Clock>>,
+    max_age: Option<Duration>,
+    writers: AtomicUsize,
+    // threads in `wait_for` plus registered wakers; `notify` does nothing while it is zero
+    waiting: AtomicUsize,
+    #[cfg(feature = "std")]
+    notify: std::sync::Mutex<()>,
+    #[cfg(feature = "std")]
+    condvar: std::sync::Condvar,
+    wakers: Lock<L, Vec<Waker>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Shared<T, L> {
+    fn update<R>(&self, f: impl FnOnce(&mut buffer::Buffer<T>) -> R) -> R {
+        let mut buffer = self.buffer.write();
+        let result = f(&mut buffer);
+        buffer.observe();
+        drop(buffer);
+        self.notify();
+        result
+    }
+    // stores `value` under the write lock; the evicted value is only kept if `on_evict` wants it
+    fn store(&self, buffer: &mut buffer::Buffer<T>, value: T, evicted: &mut Vec<T>) {
+        self.hooks.push(&value);
+        let overwritten = buffer.push(value, self.now());
+        // a zero capacity buffer hands back the pushed value itself, which overwrote nothing
+        buffer
+            .stats
+            .push(overwritten.is_some() && buffer.capacity() > 0);
+        evicted.extend(overwritten.filter(|_| self.hooks.evicts()));
+    }
+    fn pop<V>(&self, f: impl FnOnce(&mut buffer::Buffer<T>) -> V) -> V {
+        self.update_live(|buffer| {
+            let len = buffer.len();
+            let result = f(buffer);
+            buffer.stats.pop(len - buffer.len());
+            result
+        })
+    }
+    fn notify(&self) {
+        // pairs with the fence after `waiting` is raised: either the waiter sees the change
+        // that led here, or this sees the waiter
+        fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        #[cfg(feature = "std")]
+        drop(self.notify.lock());
+        self.wake_all();
+    }
+    // callers must have released the buffer lock, or hold the notify mutex
+    fn wake_all(&self) {
+        #[cfg(feature = "std")]
+        self.condvar.notify_all();
+        let wakers = mem::take(&mut *self.wakers.write());
+        self.waiting.fetch_sub(wakers.len(), Ordering::Relaxed);
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+    fn writers(&self) -> usize {
+        self.writers.load(Ordering::Acquire)
+    }
+    fn readers(self: &Arc<Self>) -> usize {
+        Arc::strong_count(self).saturating_sub(self.writers())
+    }
 }
 
+#[cfg(feature = "alloc")]
+pub struct Builder<T, L = DefaultLock> {
+    capacity: usize,
+    overflow: Overflow,
+    hooks: Hooks<T>,
+    clock: Option<Box<dyn Clock>>,
+    max_age: Option<Duration>,
+    marker: PhantomData<fn() -> L>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Builder<T, L> {
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+    /// Guards the buffer with the lock `M` instead of `DefaultLock`.
+    pub fn lock<M: RawLock>(self) -> Builder<T, M> {
+        Builder {
+            capacity: self.capacity,
+            overflow: self.overflow,
+            hooks: self.hooks,
+            clock: self.clock,
+            max_age: self.max_age,
+            marker: PhantomData,
+        }
+    }
+    pub fn build(self) -> Buffer<T, L> {
+        let clock = match (self.clock, self.max_age) {
+            #[cfg(feature = "std")]
+            (None, Some(_)) => Some(Box::new(SystemClock::new()) as Box<dyn Clock>),
+            #[cfg(not(feature = "std"))]
+            (None, Some(_)) => panic!("max_age needs a clock without the std feature"),
+            (clock, _) => clock,
+        };
+        Buffer {
+            shared: Arc::new(Shared {
+                buffer: Lock::new(buffer::Buffer::new(self.capacity, clock.is_some())),
+                overflow: self.overflow,
+                hooks: self.hooks,
+                clock,
+                max_age: self.max_age,
+                writers: AtomicUsize::new(1),
+                waiting: AtomicUsize::new(0),
+                #[cfg(feature = "std")]
+                notify: std::sync::Mutex::new(()),
+                #[cfg(feature = "std")]
+                condvar: std::sync::Condvar::new(),
+                wakers: Lock::new(Vec::new()),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub struct Buffer<T, L: RawLock = DefaultLock> {
+    shared: Arc<Shared<T, L>>,
+}
+
+#[cfg(feature = "alloc")]
 impl<T> Buffer<T> {
+    /// A zero capacity buffer is a sink: it accepts and immediately drops every pushed value.
     pub fn new(capacity: usize) -> Self {
+        Self::builder(capacity).build()
+    }
+    pub fn builder(capacity: usize) -> Builder<T> {
+        Builder {
+            capacity,
+            overflow: Overflow::default(),
+            hooks: Hooks::default(),
+            clock: None,
+            max_age: None,
+            marker: PhantomData,
+        }
+    }
+    pub fn try_new(capacity: usize) -> Result<Self, ZeroCapacity> {
+        match capacity {
+            0 => Err(ZeroCapacity),
+            _ => Ok(Self::new(capacity)),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Buffer<T, L> {
+    pub fn writer(&self) -> Writer<T, L> {
+        Writer {
+            buffer: self.clone(),
+        }
+    }
+    pub fn reader(&self) -> Reader<T, L> {
+        Reader {
+            shared: self.shared.clone(),
+        }
+    }
+    /// Number of handles that can write: `Buffer`s and `Writer`s.
+    pub fn strong_count(&self) -> usize {
+        self.shared.writers()
+    }
+    /// Number of read-only `Reader` handles.
+    pub fn weak_count(&self) -> usize {
+        self.shared.readers()
+    }
+    pub fn len(&self) -> usize {
+        self.shared.read().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.shared.read().is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        self.shared.read().capacity()
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        self.shared.read().fill_level()
+    }
+    pub fn overflow(&self) -> Overflow {
+        self.shared.overflow
+    }
+    /// Pushes according to the overflow policy. A value rejected under `Overflow::RejectNew`
+    /// or after an `Overflow::Block` timeout is dropped; use `try_push` to get it back.
+    pub fn push(&self, value: T) {
+        let _ = self.shared.try_push(value);
+    }
+    pub fn try_push(&self, value: T) -> Result<(), PushError<T>> {
+        self.shared.try_push(value)
+    }
+    /// Atomically offers `value` to `merge` together with the newest element. If `merge`
+    /// returns `true` the value is considered folded into the head and `Ok(true)` is returned,
+    /// otherwise the value is pushed according to the overflow policy.
+    pub fn push_or_merge(
+        &self,
+        value: T,
+        merge: impl FnMut(&mut T, &T) -> bool,
+    ) -> Result<bool, PushError<T>> {
+        self.shared.push_or_merge(value, merge)
+    }
+    pub fn pop_front(&self) -> Option<T> {
+        self.shared.pop(|buffer| buffer.pop_front())
+    }
+    pub fn pop_back(&self) -> Option<T> {
+        self.shared.pop(|buffer| buffer.pop_back())
+    }
+    /// Removes up to `n` of the oldest elements, oldest first.
+    pub fn pop_n(&self, n: usize) -> Vec<T> {
+        self.shared.pop(|buffer| buffer.pop_n(n))
+    }
+    /// Removes all elements, oldest first.
+    pub fn drain(&self) -> Vec<T> {
+        self.shared.pop(|buffer| buffer.drain())
+    }
+    /// Changes the capacity, keeping the newest elements in order. Returns the oldest
+    /// elements that no longer fit, oldest first.
+    pub fn resize(&self, capacity: usize) -> Vec<T> {
+        self.shared.pop(|buffer| buffer.resize(capacity))
+    }
+    pub fn clear(&self) {
+        self.shared.update(|buffer| {
+            buffer.clear();
+            buffer.stats.clear();
+        });
+        self.shared.hooks.clear();
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Clone, L: RawLock> Buffer<T, L> {
+    /// Pushes according to the overflow policy and returns how many values were accepted.
+    pub fn push_slice(&self, slice: &[T]) -> usize {
+        self.shared.push_slice(slice)
+    }
+    pub fn head(&self) -> Option<T> {
+        self.shared.read().head()
+    }
+    pub fn snapshot(&self) -> Vec<T> {
+        self.shared.read().snapshot()
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Clone for Buffer<T, L> {
+    fn clone(&self) -> Self {
+        self.shared.writers.fetch_add(1, Ordering::AcqRel);
         Self {
-            buffer: Arc::new(RwLock::new(buffer::Buffer::new(capacity))),
+            shared: self.shared.clone(),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Drop for Buffer<T, L> {
+    fn drop(&mut self) {
+        if self.shared.writers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.notify();
         }
     }
+}
+
+#[cfg(feature = "alloc")]
+/// Producer side handle: counts towards `strong_count`.
+pub struct Writer<T, L: RawLock = DefaultLock> {
+    buffer: Buffer<T, L>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Writer<T, L> {
+    pub fn reader(&self) -> Reader<T, L> {
+        self.buffer.reader()
+    }
+    pub fn strong_count(&self) -> usize {
+        self.buffer.strong_count()
+    }
+    pub fn weak_count(&self) -> usize {
+        self.buffer.weak_count()
+    }
     pub fn len(&self) -> usize {
-        self.buffer.read().len()
+        self.buffer.len()
     }
     pub fn is_empty(&self) -> bool {
-        self.buffer.read().is_empty()
+        self.buffer.is_empty()
     }
     pub fn capacity(&self) -> usize {
-        self.buffer.read().capacity()
+        self.buffer.capacity()
     }
     pub fn fill_level(&self) -> FillLevel {
-        self.buffer.read().fill_level()
+        self.buffer.fill_level()
+    }
+    pub fn overflow(&self) -> Overflow {
+        self.buffer.overflow()
     }
     pub fn push(&self, value: T) {
-        self.buffer.write().push(value)
+        self.buffer.push(value)
+    }
+    pub fn try_push(&self, value: T) -> Result<(), PushError<T>> {
+        self.buffer.try_push(value)
+    }
+    pub fn push_or_merge(
+        &self,
+        value: T,
+        merge: impl FnMut(&mut T, &T) -> bool,
+    ) -> Result<bool, PushError<T>> {
+        self.buffer.push_or_merge(value, merge)
+    }
+    pub fn clear(&self) {
+        self.buffer.clear()
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Clone, L: RawLock> Writer<T, L> {
+    pub fn push_slice(&self, slice: &[T]) -> usize {
+        self.buffer.push_slice(slice)
     }
 }
-impl<T: Clone> Buffer<T> {
-    pub fn push_slice(&self, slice: &[T]) {
-        let mut lock = self.buffer.write();
-        for value in slice {
-            lock.push(value.clone())
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Clone for Writer<T, L> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
         }
     }
+}
+
+#[cfg(feature = "alloc")]
+/// Read-only handle: counts towards `weak_count`.
+pub struct Reader<T, L: RawLock = DefaultLock> {
+    shared: Arc<Shared<T, L>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Reader<T, L> {
+    pub fn strong_count(&self) -> usize {
+        self.shared.writers()
+    }
+    pub fn weak_count(&self) -> usize {
+        self.shared.readers()
+    }
+    /// True once every `Buffer` and `Writer` handle has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.strong_count() == 0
+    }
+    pub fn len(&self) -> usize {
+        self.shared.read().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.shared.read().is_empty()
+    }
+    pub fn capacity(&self) -> usize {
+        self.shared.read().capacity()
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        self.shared.read().fill_level()
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Clone, L: RawLock> Reader<T, L> {
     pub fn head(&self) -> Option<T> {
-        self.buffer.read().head()
+        self.shared.read().head()
     }
     pub fn snapshot(&self) -> Vec<T> {
-        self.buffer.read().snapshot()
+        self.shared.read().snapshot()
     }
-    pub fn clear(&self) {
-        self.buffer.write().clear()
+}
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Clone for Reader<T, L> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
     }
 }
 
+#[cfg(feature = "alloc")]
 mod buffer {
 
+    use core::mem::MaybeUninit;
+    use core::ops::{Bound, Range, RangeBounds};
+    use core::ptr;
+
     use super::*;
+    use crate::stats::Counters;
 
     pub struct Buffer<T> {
-        buffer: Vec<T>,
+        buffer: Box<[MaybeUninit<T>]>,
+        // sequence number of the value in the matching slot
+        seqs: Box<[u64]>,
+        // push time of the value in the matching slot, for buffers with a clock
+        times: Option<Box<[Duration]>>,
         len: usize,
         pos: usize,
+        pushed: u64,
+        cleared: u64,
+        pub stats: Counters,
     }
     impl<T> Buffer<T> {
-        pub fn new(capacity: usize) -> Self {
-            let mut vec = Vec::with_capacity(capacity);
-            #[allow(clippy::uninit_vec)]
-            unsafe {
-                vec.set_len(capacity);
-            }
+        pub fn new(capacity: usize, timed: bool) -> Self {
             Self {
-                buffer: vec,
+                buffer: Box::new_uninit_slice(capacity),
+                seqs: vec![0; capacity].into_boxed_slice(),
+                times: timed.then(|| vec![Duration::ZERO; capacity].into_boxed_slice()),
                 len: 0,
                 pos: 0,
+                pushed: 0,
+                cleared: 0,
+                stats: Counters::new(),
             }
         }
         pub fn len(&self) -> usize {
@@ -81,9 +551,55 @@ mod buffer {
             self.len() == 0
         }
         pub fn capacity(&self) -> usize {
-            self.buffer.capacity()
+            self.buffer.len()
+        }
+        pub fn pushed(&self) -> u64 {
+            self.pushed
         }
-        pub fn inc_pos(&mut self) {
+        pub fn cleared(&self) -> u64 {
+            self.cleared
+        }
+        // physical index of the element at logical index `index`, 0 being the oldest
+        fn physical(&self, index: usize) -> Option<usize> {
+            if index >= self.len {
+                return None;
+            }
+            let index = self.tail() + index;
+            Some(match index.checked_sub(self.capacity()) {
+                Some(wrapped) => wrapped,
+                None => index,
+            })
+        }
+        pub fn get(&self, index: usize) -> Option<&T> {
+            let index = self.physical(index)?;
+            // SAFETY: physical only maps to initialized slots
+            Some(unsafe { self.buffer[index].assume_init_ref() })
+        }
+        pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+            let index = self.physical(index)?;
+            // SAFETY: physical only maps to initialized slots
+            Some(unsafe { self.buffer[index].assume_init_mut() })
+        }
+        pub fn head_mut(&mut self) -> Option<&mut T> {
+            self.get_mut(self.len.checked_sub(1)?)
+        }
+        pub fn seq(&self, index: usize) -> Option<u64> {
+            Some(self.seqs[self.physical(index)?])
+        }
+        // logical index of the first element with a sequence number of at least `seq`
+        pub fn position(&self, seq: u64) -> usize {
+            let (older, newer) = self.seq_slices();
+            match older.last() {
+                Some(last) if *last >= seq => older.partition_point(|s| *s < seq),
+                _ => older.len() + newer.partition_point(|s| *s < seq),
+            }
+        }
+        // the (older, newer) halves restricted to a logical range
+        pub fn range(&self, range: impl RangeBounds<usize>) -> Option<(&[T], &[T])> {
+            let range = bounds(range, self.len)?;
+            Some(sub_slices(self.as_slices(), range))
+        }
+        fn inc_pos(&mut self) {
             self.pos += 1;
             let capacity = self.capacity();
             if self.pos == capacity {
@@ -93,53 +609,286 @@ mod buffer {
                 self.len += 1;
             }
         }
-        pub fn fill_level(&self) -> FillLevel {
-            let capacity = self.capacity();
-            match self.len() {
-                0 => FillLevel::Empty,
-                x if x == capacity => FillLevel::Full,
-                _ => FillLevel::Partial,
+        // physical index of the oldest element
+        fn tail(&self) -> usize {
+            if self.len <= self.pos {
+                self.pos - self.len
+            } else {
+                self.pos + self.capacity() - self.len
             }
         }
-        pub fn push(&mut self, value: T) {
-            *&mut self.buffer[self.pos] = value;
+        pub fn is_full(&self) -> bool {
+            self.len == self.capacity()
+        }
+        pub fn fill_level(&self) -> FillLevel {
+            FillLevel::new(self.len(), self.capacity())
+        }
+        // returns the overwritten oldest element, if the buffer was full
+        pub fn push(&mut self, value: T, time: Duration) -> Option<T> {
+            let seq = self.pushed;
+            self.pushed += 1;
+            if self.capacity() == 0 {
+                return Some(value);
+            }
+            self.seqs[self.pos] = seq;
+            if let Some(times) = &mut self.times {
+                times[self.pos] = time;
+            }
+            let full = self.len == self.capacity();
+            let slot = &mut self.buffer[self.pos];
+            let evicted = if full {
+                // SAFETY: a full buffer has every slot initialized
+                Some(unsafe { slot.assume_init_read() })
+            } else {
+                None
+            };
+            slot.write(value);
             self.inc_pos();
+            evicted
+        }
+        pub fn pop_front(&mut self) -> Option<T> {
+            if self.is_empty() {
+                return None;
+            }
+            let tail = self.tail();
+            self.len -= 1;
+            // SAFETY: the oldest slot is initialized and no longer tracked by `len`
+            Some(unsafe { self.buffer[tail].assume_init_read() })
+        }
+        pub fn pop_back(&mut self) -> Option<T> {
+            if self.is_empty() {
+                return None;
+            }
+            self.pos = match self.pos {
+                0 => self.capacity() - 1,
+                pos => pos - 1,
+            };
+            self.len -= 1;
+            // SAFETY: the newest slot is initialized and no longer tracked by `len`
+            Some(unsafe { self.buffer[self.pos].assume_init_read() })
+        }
+        // re-appends values in their original order, keeping their sequence numbers and times
+        pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+            for _ in 0..self.len {
+                let tail = self.tail();
+                let seq = self.seqs[tail];
+                let value = self.pop_front().unwrap();
+                if f(&value) {
+                    self.seqs[self.pos] = seq;
+                    if let Some(times) = &mut self.times {
+                        times[self.pos] = times[tail];
+                    }
+                    self.buffer[self.pos].write(value);
+                    self.inc_pos();
+                }
+            }
+        }
+        pub fn pop_n(&mut self, n: usize) -> Vec<T> {
+            let n = n.min(self.len);
+            let mut out = Vec::with_capacity(n);
+            for _ in 0..n {
+                out.extend(self.pop_front());
+            }
+            out
+        }
+        pub fn drain(&mut self) -> Vec<T> {
+            self.pop_n(self.len)
+        }
+        // re-linearizes into new storage, returning the oldest elements that no longer fit
+        pub fn resize(&mut self, capacity: usize) -> Vec<T> {
+            let evicted = self.pop_n(self.len.saturating_sub(capacity));
+            let mut buffer = Box::new_uninit_slice(capacity);
+            let mut seqs = vec![0; capacity].into_boxed_slice();
+            let (older, newer) = self.ranges();
+            let split = older.len();
+            // SAFETY: the initialized elements move to the front of the new storage, which
+            // is large enough after the eviction; the old storage never drops its contents
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.buffer[older.clone()].as_ptr(),
+                    buffer.as_mut_ptr(),
+                    split,
+                );
+                ptr::copy_nonoverlapping(
+                    self.buffer[newer.clone()].as_ptr(),
+                    buffer[split..].as_mut_ptr(),
+                    newer.len(),
+                );
+            }
+            seqs[..split].copy_from_slice(&self.seqs[older.clone()]);
+            seqs[split..self.len].copy_from_slice(&self.seqs[newer.clone()]);
+            if let Some(old) = &self.times {
+                let mut times = vec![Duration::ZERO; capacity].into_boxed_slice();
+                times[..split].copy_from_slice(&old[older]);
+                times[split..self.len].copy_from_slice(&old[newer]);
+                self.times = Some(times);
+            }
+            self.buffer = buffer;
+            self.seqs = seqs;
+            self.pos = if self.len == capacity { 0 } else { self.len };
+            evicted
         }
         pub fn clear(&mut self) {
+            let (older, newer) = self.as_mut_slices();
+            let (older, newer) = (older as *mut [T], newer as *mut [T]);
             self.len = 0;
             self.pos = 0;
+            self.cleared += 1;
+            // SAFETY: both slices were initialized and are no longer tracked by `len`
+            unsafe {
+                ptr::drop_in_place(older);
+                ptr::drop_in_place(newer);
+            }
+        }
+        // physical ranges of the (older, newer) halves; newer always ends before older starts
+        fn ranges(&self) -> (Range<usize>, Range<usize>) {
+            let tail = self.tail();
+            if tail < self.pos || self.is_empty() {
+                (tail..self.pos, 0..0)
+            } else {
+                (tail..self.capacity(), 0..self.pos)
+            }
+        }
+        // initialized elements as (older, newer) halves in chronological order
+        pub fn as_slices(&self) -> (&[T], &[T]) {
+            let (older, newer) = self.ranges();
+            // SAFETY: the slots between tail and pos are initialized
+            unsafe {
+                (
+                    assume_init(&self.buffer[older]),
+                    assume_init(&self.buffer[newer]),
+                )
+            }
+        }
+        pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+            let (older, newer) = self.ranges();
+            let (front, back) = self.buffer.split_at_mut(older.start);
+            // SAFETY: the slots between tail and pos are initialized
+            unsafe {
+                (
+                    assume_init_mut(&mut back[..older.len()]),
+                    assume_init_mut(&mut front[newer]),
+                )
+            }
+        }
+        fn seq_slices(&self) -> (&[u64], &[u64]) {
+            let (older, newer) = self.ranges();
+            (&self.seqs[older], &self.seqs[newer])
+        }
+        // push times as (older, newer) halves, `None` without a clock
+        pub fn time_slices(&self) -> Option<(&[Duration], &[Duration])> {
+            let times = self.times.as_ref()?;
+            let (older, newer) = self.ranges();
+            Some((&times[older], &times[newer]))
+        }
+        // number of the oldest elements pushed before `cutoff`
+        pub fn expired(&self, cutoff: Duration) -> usize {
+            let Some((older, newer)) = self.time_slices() else {
+                return 0;
+            };
+            match older.last() {
+                Some(last) if *last >= cutoff => older.partition_point(|t| *t < cutoff),
+                _ => older.len() + newer.partition_point(|t| *t < cutoff),
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    impl<T: Copy> Buffer<T> {
+        // pushes a whole slice in at most two copies, split at the wrap point; only the last
+        // `capacity` values are kept. Returns how many values were overwritten or dropped.
+        pub fn push_copied(&mut self, slice: &[T], time: Duration) -> usize {
+            let capacity = self.capacity();
+            let overwritten = (self.len + slice.len()).saturating_sub(capacity);
+            let skipped = slice.len().saturating_sub(capacity);
+            let mut seq = self.pushed + skipped as u64;
+            self.pushed += slice.len() as u64;
+            let slice = &slice[skipped..];
+            if slice.is_empty() {
+                return overwritten;
+            }
+            let (first, second) = slice.split_at(slice.len().min(capacity - self.pos));
+            for (start, values) in [(self.pos, first), (0, second)] {
+                let range = start..start + values.len();
+                // SAFETY: `range` is within the storage and `values` has its length; `T: Copy`
+                // has no drop glue, so initialized slots are simply overwritten
+                unsafe {
+                    ptr::copy_nonoverlapping(
+                        values.as_ptr(),
+                        self.buffer[range.clone()].as_mut_ptr().cast(),
+                        values.len(),
+                    );
+                }
+                for slot in &mut self.seqs[range.clone()] {
+                    *slot = seq;
+                    seq += 1;
+                }
+                if let Some(times) = &mut self.times {
+                    times[range].fill(time);
+                }
+            }
+            self.len = (self.len + slice.len()).min(capacity);
+            self.pos = (self.pos + slice.len()) % capacity;
+            overwritten
         }
     }
     impl<T: Clone> Buffer<T> {
         pub fn head(&self) -> Option<T> {
-            match self.fill_level() {
-                FillLevel::Empty => None,
-                _ => self
-                    .buffer
-                    .get(if self.pos == 0 {
-                        self.capacity() - 1
-                    } else {
-                        self.pos - 1
-                    })
-                    .cloned(),
-            }
+            let (older, newer) = self.as_slices();
+            newer.last().or(older.last()).cloned()
         }
         pub fn snapshot(&self) -> Vec<T> {
-            let mut out = vec![];
-            match self.fill_level() {
-                FillLevel::Partial => out.append(&mut self.buffer[..self.pos].to_vec()),
-                FillLevel::Full => {
-                    out.append(&mut self.buffer[self.pos..self.capacity()].to_vec());
-                    out.append(&mut self.buffer[..self.pos].to_vec());
-                }
-                _ => {}
-            }
+            let mut out = Vec::with_capacity(self.len());
+            self.snapshot_into(&mut out);
             out
         }
+        pub fn snapshot_into(&self, out: &mut Vec<T>) {
+            let (older, newer) = self.as_slices();
+            out.clear();
+            out.extend_from_slice(older);
+            out.extend_from_slice(newer);
+        }
+    }
+    impl<T> Drop for Buffer<T> {
+        fn drop(&mut self) {
+            self.clear();
+        }
+    }
+
+    // a logical range as start..end, `None` if it is out of bounds for `len`
+    pub fn bounds(range: impl RangeBounds<usize>, len: usize) -> Option<Range<usize>> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        (start <= end && end <= len).then_some(start..end)
+    }
+    // the parts of (older, newer) halves that fall into a logical range
+    pub fn sub_slices<'a, X>(
+        (older, newer): (&'a [X], &'a [X]),
+        range: Range<usize>,
+    ) -> (&'a [X], &'a [X]) {
+        let split = older.len();
+        (
+            &older[range.start.min(split)..range.end.min(split)],
+            &newer[range.start.saturating_sub(split)..range.end.saturating_sub(split)],
+        )
+    }
+
+    unsafe fn assume_init<T>(slice: &[MaybeUninit<T>]) -> &[T] {
+        &*(slice as *const [MaybeUninit<T>] as *const [T])
+    }
+    unsafe fn assume_init_mut<T>(slice: &mut [MaybeUninit<T>]) -> &mut [T] {
+        &mut *(slice as *mut [MaybeUninit<T>] as *mut [T])
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
 
@@ -163,4 +912,162 @@ mod tests {
         buffer.clear();
         assert!(buffer.is_empty());
     }
+
+    #[test]
+    fn drops() {
+        let value = Arc::new(());
+        let buffer = Buffer::new(2);
+
+        buffer.push_slice(&[value.clone(), value.clone(), value.clone()]);
+        assert_eq!(Arc::strong_count(&value), 3);
+
+        buffer.clear();
+        assert_eq!(Arc::strong_count(&value), 1);
+
+        buffer.push(value.clone());
+        drop(buffer);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn strings() {
+        let buffer = Buffer::new(2);
+        for line in ["a", "b", "c"] {
+            buffer.push(line.to_string());
+        }
+        assert_eq!(buffer.head(), Some("c".to_string()));
+        assert_eq!(buffer.snapshot(), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        assert_eq!(Buffer::<u8>::try_new(0).err(), Some(ZeroCapacity));
+
+        let value = Arc::new(());
+        let buffer = Buffer::new(0);
+        buffer.push(value.clone());
+        buffer.push_slice(&[value.clone(), value.clone()]);
+        assert_eq!(Arc::strong_count(&value), 1);
+        assert_eq!(buffer.capacity(), 0);
+        assert_eq!(buffer.fill_level(), FillLevel::Empty);
+        assert_eq!(buffer.head(), None);
+        assert!(buffer.snapshot().is_empty());
+    }
+
+    #[test]
+    fn zero_sized() {
+        let buffer = Buffer::new(3);
+        assert_eq!(buffer.capacity(), 3);
+
+        buffer.push(());
+        buffer.push(());
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.fill_level(), FillLevel::Partial);
+        assert_eq!(buffer.head(), Some(()));
+
+        buffer.push_slice(&[(), ()]);
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.fill_level(), FillLevel::Full);
+        assert_eq!(buffer.snapshot(), vec![(); 3]);
+    }
+
+    #[test]
+    fn pop() {
+        let buffer = Buffer::new(3);
+        assert_eq!(buffer.pop_front(), None);
+        assert_eq!(buffer.pop_back(), None);
+
+        buffer.push_slice(&[1, 2, 3, 4]);
+        assert_eq!(buffer.pop_front(), Some(2));
+        assert_eq!(buffer.pop_back(), Some(4));
+        assert_eq!(buffer.snapshot(), vec![3]);
+
+        buffer.push_slice(&[5, 6]);
+        assert_eq!(buffer.snapshot(), vec![3, 5, 6]);
+        assert_eq!(buffer.fill_level(), FillLevel::Full);
+        assert_eq!(buffer.head(), Some(6));
+
+        assert_eq!(buffer.pop_n(2), vec![3, 5]);
+        assert_eq!(buffer.len(), 1);
+
+        buffer.push_slice(&[7, 8, 9]);
+        assert_eq!(buffer.drain(), vec![7, 8, 9]);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.pop_n(1), vec![]);
+    }
+
+    #[test]
+    fn pop_without_clone() {
+        struct Token(u8);
+
+        let buffer = Buffer::new(2);
+        buffer.push(Token(1));
+        buffer.push(Token(2));
+        buffer.push(Token(3));
+        assert_eq!(buffer.pop_front().map(|token| token.0), Some(2));
+        assert_eq!(buffer.pop_back().map(|token| token.0), Some(3));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn handles() {
+        let buffer = Buffer::new(2);
+        let writer = buffer.writer();
+        let reader = buffer.reader();
+        let clone = buffer.clone();
+        assert_eq!(buffer.strong_count(), 3);
+        assert_eq!(buffer.weak_count(), 1);
+
+        writer.push_slice(&[1, 2, 3]);
+        assert_eq!(reader.snapshot(), vec![2, 3]);
+        assert_eq!(clone.head(), Some(3));
+
+        let second = reader.clone();
+        assert_eq!(reader.weak_count(), 2);
+
+        drop(buffer);
+        drop(clone);
+        assert!(!reader.is_closed());
+        drop(writer);
+        assert!(reader.is_closed());
+        assert_eq!(second.strong_count(), 0);
+        assert_eq!(second.snapshot(), vec![2, 3]);
+    }
+
+    #[test]
+    fn resize() {
+        let buffer = Buffer::new(3);
+        buffer.push_slice(&[1, 2, 3, 4]);
+        let cursor = buffer.cursor();
+
+        assert_eq!(buffer.resize(5), vec![]);
+        assert_eq!(buffer.capacity(), 5);
+        assert_eq!(buffer.fill_level(), FillLevel::Partial);
+        buffer.push_slice(&[5, 6]);
+        assert_eq!(buffer.snapshot(), vec![2, 3, 4, 5, 6]);
+        assert_eq!(buffer.fill_level(), FillLevel::Full);
+
+        buffer.push(7);
+        assert_eq!(buffer.resize(2), vec![3, 4, 5]);
+        assert_eq!(buffer.snapshot(), vec![6, 7]);
+        assert_eq!(buffer.fill_level(), FillLevel::Full);
+        assert_eq!(buffer.head(), Some(7));
+        assert_eq!(buffer.read_since(cursor).0, vec![6, 7]);
+
+        buffer.push(8);
+        assert_eq!(buffer.snapshot(), vec![7, 8]);
+        assert_eq!(buffer.resize(0), vec![7, 8]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn resize_drops() {
+        let value = Arc::new(());
+        let buffer = Buffer::new(2);
+        buffer.push_slice(&[value.clone(), value.clone()]);
+        drop(buffer.resize(1));
+        assert_eq!(Arc::strong_count(&value), 2);
+        drop(buffer);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
 }
@@ -0,0 +1,234 @@
+use core::cell::UnsafeCell;
+use core::hint;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A raw reader-writer lock guarding the contents of a buffer.
+///
+/// # Safety
+///
+/// While the exclusive lock is held, no other lock of either kind may be granted.
+pub unsafe trait RawLock {
+    /// An unlocked lock.
+    const INIT: Self;
+
+    fn lock_shared(&self);
+    /// # Safety
+    ///
+    /// The caller must hold a shared lock.
+    unsafe fn unlock_shared(&self);
+    fn lock_exclusive(&self);
+    /// # Safety
+    ///
+    /// The caller must hold the exclusive lock.
+    unsafe fn unlock_exclusive(&self);
+}
+
+/// The lock `Buffer` uses unless told otherwise: parking_lot's with the `parking_lot`
+/// feature, `SpinLock` without it. Targets without atomic compare-and-swap have neither.
+#[cfg(feature = "parking_lot")]
+pub type DefaultLock = parking_lot::RawRwLock;
+#[cfg(all(not(feature = "parking_lot"), target_has_atomic = "ptr"))]
+pub type DefaultLock = SpinLock;
+
+#[cfg(feature = "parking_lot")]
+unsafe impl RawLock for parking_lot::RawRwLock {
+    const INIT: Self = <Self as parking_lot::lock_api::RawRwLock>::INIT;
+
+    fn lock_shared(&self) {
+        parking_lot::lock_api::RawRwLock::lock_shared(self)
+    }
+    unsafe fn unlock_shared(&self) {
+        parking_lot::lock_api::RawRwLock::unlock_shared(self)
+    }
+    fn lock_exclusive(&self) {
+        parking_lot::lock_api::RawRwLock::lock_exclusive(self)
+    }
+    unsafe fn unlock_exclusive(&self) {
+        parking_lot::lock_api::RawRwLock::unlock_exclusive(self)
+    }
+}
+
+/// A reader-writer spin lock for targets without an operating system.
+///
+/// Waiting threads busy-loop, and a steady stream of readers can starve a writer. On a single
+/// core it must not be taken from an interrupt handler that may preempt a holder.
+#[cfg(target_has_atomic = "ptr")]
+pub struct SpinLock {
+    // WRITER while exclusively locked, otherwise twice the number of readers
+    state: AtomicUsize,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+const WRITER: usize = 1;
+
+#[cfg(target_has_atomic = "ptr")]
+unsafe impl RawLock for SpinLock {
+    const INIT: Self = SpinLock {
+        state: AtomicUsize::new(0),
+    };
+
+    fn lock_shared(&self) {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & WRITER != 0 {
+                hint::spin_loop();
+                state = self.state.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.state.compare_exchange_weak(
+                state,
+                state + 2,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => state = current,
+            }
+        }
+    }
+    unsafe fn unlock_shared(&self) {
+        self.state.fetch_sub(2, Ordering::Release);
+    }
+    fn lock_exclusive(&self) {
+        while self
+            .state
+            .compare_exchange_weak(0, WRITER, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+    }
+    unsafe fn unlock_exclusive(&self) {
+        self.state.store(0, Ordering::Release);
+    }
+}
+
+pub(crate) struct Lock<L, T> {
+    raw: L,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: the raw lock serializes writers and only lets readers share `&T`
+unsafe impl<L: RawLock + Send, T: Send> Send for Lock<L, T> {}
+unsafe impl<L: RawLock + Sync, T: Send + Sync> Sync for Lock<L, T> {}
+
+impl<L: RawLock, T> Lock<L, T> {
+    pub(crate) const fn new(data: T) -> Self {
+        Self {
+            raw: L::INIT,
+            data: UnsafeCell::new(data),
+        }
+    }
+    pub(crate) fn read(&self) -> SharedGuard<'_, L, T> {
+        self.raw.lock_shared();
+        SharedGuard {
+            lock: self,
+            marker: PhantomData,
+        }
+    }
+    pub(crate) fn write(&self) -> ExclusiveGuard<'_, L, T> {
+        self.raw.lock_exclusive();
+        ExclusiveGuard {
+            lock: self,
+            marker: PhantomData,
+        }
+    }
+}
+
+// guards are not `Send`: some raw locks must be unlocked on the thread that locked them
+pub(crate) struct SharedGuard<'a, L: RawLock, T> {
+    lock: &'a Lock<L, T>,
+    marker: PhantomData<*const ()>,
+}
+
+unsafe impl<L: RawLock + Sync, T: Sync> Sync for SharedGuard<'_, L, T> {}
+
+impl<L: RawLock, T> Deref for SharedGuard<'_, L, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the shared lock is held for the lifetime of the guard
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<L: RawLock, T> Drop for SharedGuard<'_, L, T> {
+    fn drop(&mut self) {
+        // SAFETY: taken in `Lock::read`
+        unsafe { self.lock.raw.unlock_shared() }
+    }
+}
+
+pub(crate) struct ExclusiveGuard<'a, L: RawLock, T> {
+    lock: &'a Lock<L, T>,
+    marker: PhantomData<*const ()>,
+}
+
+unsafe impl<L: RawLock + Sync, T: Sync> Sync for ExclusiveGuard<'_, L, T> {}
+
+impl<L: RawLock, T> Deref for ExclusiveGuard<'_, L, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the exclusive lock is held for the lifetime of the guard
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<L: RawLock, T> DerefMut for ExclusiveGuard<'_, L, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the exclusive lock is held for the lifetime of the guard
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<L: RawLock, T> Drop for ExclusiveGuard<'_, L, T> {
+    fn drop(&mut self) {
+        // SAFETY: taken in `Lock::write`
+        unsafe { self.lock.raw.unlock_exclusive() }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{Buffer, SyncArrayBuffer};
+
+    #[test]
+    fn spin_lock() {
+        let lock = Lock::<SpinLock, _>::new(0u64);
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        *lock.write() += 1;
+                        assert!(*lock.read() > 0);
+                    }
+                });
+            }
+        });
+        assert_eq!(*lock.read(), 4000);
+
+        let first = lock.read();
+        let second = lock.read();
+        assert_eq!(*first + *second, 8000);
+    }
+
+    #[test]
+    fn buffer() {
+        let buffer = Buffer::builder(2).lock::<SpinLock>().build();
+        let reader = buffer.reader();
+        buffer.push_slice(&[1, 2, 3]);
+        assert_eq!(reader.snapshot(), vec![2, 3]);
+        assert_eq!(buffer.read().head(), Some(&3));
+
+        static ARRAY: SyncArrayBuffer<u8, 2, SpinLock> = SyncArrayBuffer::with_lock();
+        ARRAY.push_slice(&[1, 2, 3]);
+        assert_eq!(ARRAY.snapshot(), vec![2, 3]);
+    }
+}
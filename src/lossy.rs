@@ -0,0 +1,194 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::sync::atomic::{fence, AtomicU64, Ordering};
+
+use super::*;
+
+struct Slot<T> {
+    // 0 until first written, 2 * seq + 1 while seq is being written, 2 * seq + 2 once published
+    stamp: AtomicU64,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+struct Ring<T> {
+    slots: Box<[Slot<T>]>,
+    claimed: AtomicU64,
+}
+
+// SAFETY: values are only read back through the stamp check, and `T: Copy` has no drop glue
+unsafe impl<T: Copy + Send> Send for Ring<T> {}
+unsafe impl<T: Copy + Send> Sync for Ring<T> {}
+
+/// A lossy ring for many concurrent producers, in the style of a flight recorder.
+///
+/// Producers claim a sequence number with a single atomic increment and publish into the slot
+/// it maps to, without any lock or waiting: a value whose slot another producer is still
+/// writing is dropped. Readers validate every slot by its sequence stamp, so values
+/// that are torn by a concurrent write or already overwritten are skipped rather than returned.
+pub struct LossyBuffer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T: Copy> LossyBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        let slots = (0..capacity)
+            .map(|_| Slot {
+                stamp: AtomicU64::new(0),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+        Self {
+            ring: Arc::new(Ring {
+                slots,
+                claimed: AtomicU64::new(0),
+            }),
+        }
+    }
+    fn slot(&self, seq: u64) -> &Slot<T> {
+        &self.ring.slots[(seq % self.ring.slots.len() as u64) as usize]
+    }
+    pub fn push(&self, value: T) {
+        if self.ring.slots.is_empty() {
+            return;
+        }
+        let seq = self.ring.claimed.fetch_add(1, Ordering::Relaxed);
+        let slot = self.slot(seq);
+        let writing = 2 * seq + 1;
+        let mut current = slot.stamp.load(Ordering::Relaxed);
+        loop {
+            if current >= writing || current % 2 == 1 {
+                // a producer a full lap ahead already owns the slot, or an older one is still
+                // writing it; either way this value is lost rather than waited on
+                return;
+            }
+            match slot.stamp.compare_exchange_weak(
+                current,
+                writing,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(stamp) => current = stamp,
+            }
+        }
+        fence(Ordering::Release);
+        // SAFETY: the odd stamp gives this producer exclusive write access to the slot
+        unsafe { ptr::write(slot.value.get(), MaybeUninit::new(value)) };
+        slot.stamp.store(writing + 1, Ordering::Release);
+    }
+    fn read(&self, seq: u64) -> Option<T> {
+        let slot = self.slot(seq);
+        let stamp = slot.stamp.load(Ordering::Acquire);
+        if stamp != 2 * seq + 2 {
+            return None;
+        }
+        // SAFETY: the copy may be torn by a concurrent writer, it is only used once the
+        // unchanged stamp proves it is not
+        let value = unsafe { ptr::read_volatile(slot.value.get()) };
+        fence(Ordering::Acquire);
+        if slot.stamp.load(Ordering::Relaxed) != stamp {
+            return None;
+        }
+        Some(unsafe { value.assume_init() })
+    }
+    /// The published values of the last `capacity` claimed slots, oldest to newest.
+    pub fn snapshot(&self) -> Vec<T> {
+        let end = self.ring.claimed.load(Ordering::Acquire);
+        let start = end.saturating_sub(self.ring.slots.len() as u64);
+        (start..end).filter_map(|seq| self.read(seq)).collect()
+    }
+    /// Total number of values pushed, including ones since overwritten.
+    pub fn pushed(&self) -> u64 {
+        self.ring.claimed.load(Ordering::Acquire)
+    }
+    /// Number of claimed slots in the window; some may still be in the middle of a write.
+    pub fn len(&self) -> usize {
+        self.pushed().min(self.capacity() as u64) as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn capacity(&self) -> usize {
+        self.ring.slots.len()
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        FillLevel::new(self.len(), self.capacity())
+    }
+}
+
+impl<T> Clone for LossyBuffer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ring: self.ring.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test() {
+        let buffer = LossyBuffer::new(3);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.snapshot(), vec![]);
+
+        buffer.push(1);
+        buffer.push(2);
+        assert_eq!(buffer.fill_level(), FillLevel::Partial);
+        assert_eq!(buffer.snapshot(), vec![1, 2]);
+
+        for value in 3..=5 {
+            buffer.push(value);
+        }
+        assert_eq!(buffer.fill_level(), FillLevel::Full);
+        assert_eq!(buffer.pushed(), 5);
+        assert_eq!(buffer.snapshot(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let buffer = LossyBuffer::new(0);
+        buffer.push(1);
+        assert_eq!(buffer.pushed(), 0);
+        assert_eq!(buffer.snapshot(), vec![]);
+    }
+
+    #[test]
+    fn producers() {
+        let buffer = LossyBuffer::new(64);
+        let producers: Vec<_> = (0..4u64)
+            .map(|producer| {
+                let buffer = buffer.clone();
+                thread::spawn(move || {
+                    for value in 0..10_000u64 {
+                        buffer.push([producer, value, producer, value]);
+                    }
+                })
+            })
+            .collect();
+
+        while producers.iter().any(|handle| !handle.is_finished()) {
+            let snapshot = buffer.snapshot();
+            assert!(snapshot.len() <= 64);
+            for producer in 0..4 {
+                let values: Vec<_> = snapshot
+                    .iter()
+                    .filter(|entry| entry[0] == producer)
+                    .inspect(|entry| assert_eq!(entry[..2], entry[2..]))
+                    .map(|entry| entry[1])
+                    .collect();
+                assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+        }
+        for handle in producers {
+            handle.join().unwrap();
+        }
+        assert_eq!(buffer.pushed(), 40_000);
+        assert_eq!(buffer.snapshot().len(), 64);
+    }
+}
@@ -0,0 +1,408 @@
+use alloc::collections::VecDeque;
+
+use super::*;
+use crate::quantile::{Histogram, Order};
+use crate::rate::{counter_delta, Ema, Smoothing};
+
+/// Running statistics of the values in a `NumericBuffer`, taken under a single lock.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub len: usize,
+    pub sum: f64,
+    pub mean: f64,
+    /// Population variance.
+    pub variance: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+// Neumaier's compensated sum, which stays exact across adding and later subtracting values of
+// very different magnitudes
+#[derive(Default)]
+pub(crate) struct Sum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl Sum {
+    fn add(&mut self, value: f64) {
+        let sum = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - sum) + value;
+        } else {
+            self.compensation += (value - sum) + self.sum;
+        }
+        self.sum = sum;
+    }
+    pub(crate) fn get(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+pub(crate) struct Window {
+    pub(crate) values: buffer::Buffer<f64>,
+    clock: Option<Box<dyn Clock>>,
+    sum: Sum,
+    mean: f64,
+    // sum of squared differences from the mean
+    m2: f64,
+    // values removed since `mean` and `m2` were last recomputed from scratch
+    drift: usize,
+    // (sequence number, value) of the candidates for the minimum, increasing by value
+    pub(crate) min: VecDeque<(u64, f64)>,
+    // (sequence number, value) of the candidates for the maximum, decreasing by value
+    pub(crate) max: VecDeque<(u64, f64)>,
+    pub(crate) order: Option<Order>,
+    pub(crate) histogram: Option<Histogram>,
+    pub(crate) ema: Option<Ema>,
+    // increase of the values read as a counter, across every adjacent pair in the window
+    pub(crate) increase: Sum,
+}
+
+impl Window {
+    fn new(capacity: usize, clock: Option<Box<dyn Clock>>) -> Self {
+        Self {
+            values: buffer::Buffer::new(capacity, clock.is_some()),
+            clock,
+            sum: Sum::default(),
+            mean: 0.0,
+            m2: 0.0,
+            drift: 0,
+            min: VecDeque::new(),
+            max: VecDeque::new(),
+            order: None,
+            histogram: None,
+            ema: None,
+            increase: Sum::default(),
+        }
+    }
+    fn push(&mut self, value: f64) {
+        // NaN has no place in the ordering every statistic relies on, and an infinity would
+        // turn the running sum and mean into NaN long after it left the window
+        if !value.is_finite() || self.values.capacity() == 0 {
+            return;
+        }
+        let seq = self.values.pushed();
+        let time = self
+            .clock
+            .as_ref()
+            .map_or(Duration::ZERO, |clock| clock.now());
+        if let Some(previous) = self.values.head() {
+            self.increase.add(counter_delta(previous, value));
+        }
+        if let Some(ema) = &mut self.ema {
+            ema.push(value, time);
+        }
+        let old_mean = self.mean;
+        let overwritten = self.values.push(value, time);
+        if let (Some(old), Some(oldest)) = (overwritten, self.values.get(0)) {
+            self.increase.add(-counter_delta(old, *oldest));
+        }
+        match overwritten {
+            Some(overwritten) => {
+                self.sum.add(-overwritten);
+                self.sum.add(value);
+                let delta = value - overwritten;
+                self.mean += delta / self.len() as f64;
+                self.m2 += delta * (value - self.mean + overwritten - old_mean);
+                self.drift += 1;
+            }
+            None => {
+                self.sum.add(value);
+                let delta = value - old_mean;
+                self.mean += delta / self.len() as f64;
+                self.m2 += delta * (value - self.mean);
+            }
+        }
+        self.m2 = self.m2.max(0.0);
+        if self.drift >= self.len() {
+            self.recompute();
+        }
+
+        while self.min.back().is_some_and(|(_, min)| *min >= value) {
+            self.min.pop_back();
+        }
+        self.min.push_back((seq, value));
+        while self.max.back().is_some_and(|(_, max)| *max <= value) {
+            self.max.pop_back();
+        }
+        self.max.push_back((seq, value));
+        if let Some(order) = &mut self.order {
+            overwritten.inspect(|old| order.remove(*old));
+            order.insert(value);
+        }
+        if let Some(histogram) = &mut self.histogram {
+            overwritten.inspect(|old| histogram.remove(*old));
+            histogram.insert(value);
+        }
+        let oldest = self.values.seq(0).unwrap();
+        for extremes in [&mut self.min, &mut self.max] {
+            while extremes.front().is_some_and(|(seq, _)| *seq < oldest) {
+                extremes.pop_front();
+            }
+        }
+    }
+    // two passes over the window, to shed the rounding error of the rolling updates
+    fn recompute(&mut self) {
+        let (older, newer) = self.values.as_slices();
+        let mut sum = Sum::default();
+        older.iter().chain(newer).for_each(|value| sum.add(*value));
+        let mean = sum.get() / self.len() as f64;
+        self.m2 = older
+            .iter()
+            .chain(newer)
+            .map(|value| (value - mean) * (value - mean))
+            .sum();
+        let mut increase = Sum::default();
+        let pairs = older
+            .iter()
+            .chain(newer)
+            .zip(older.iter().chain(newer).skip(1));
+        pairs.for_each(|(from, to)| increase.add(counter_delta(*from, *to)));
+        self.mean = mean;
+        self.sum = sum;
+        self.increase = increase;
+        self.drift = 0;
+    }
+    fn clear(&mut self) {
+        self.order.iter_mut().for_each(Order::clear);
+        self.histogram.iter_mut().for_each(Histogram::clear);
+        self.ema.iter_mut().for_each(Ema::clear);
+        *self = Self {
+            order: self.order.take(),
+            histogram: self.histogram.take(),
+            ema: self.ema.take(),
+            ..Self::new(self.values.capacity(), self.clock.take())
+        };
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+    fn summary(&self) -> Option<Summary> {
+        Some(Summary {
+            len: self.len(),
+            sum: self.sum.get(),
+            mean: self.mean,
+            variance: self.m2 / self.len() as f64,
+            min: self.min.front()?.1,
+            max: self.max.front()?.1,
+        })
+    }
+}
+
+/// A ring buffer of `f64` that keeps its sum, mean, variance, minimum and maximum up to date as
+/// values are pushed and overwritten, so every statistic is an O(1) query.
+///
+/// The sum is compensated, and the mean and variance are recomputed from the window once as
+/// many values have been overwritten as it holds, so rounding errors do not accumulate. NaN
+/// and infinite values are ignored. Clones share the same buffer.
+pub struct NumericBuffer<L: RawLock = DefaultLock> {
+    pub(crate) window: Arc<Lock<L, Window>>,
+}
+
+pub struct NumericBuilder<L = DefaultLock> {
+    capacity: usize,
+    pub(crate) quantiles: bool,
+    pub(crate) buckets: Option<Box<[f64]>>,
+    pub(crate) clock: Option<Box<dyn Clock>>,
+    pub(crate) smoothing: Option<Smoothing>,
+    marker: PhantomData<fn() -> L>,
+}
+
+impl<L: RawLock> NumericBuilder<L> {
+    /// Guards the buffer with the lock `M` instead of `DefaultLock`.
+    pub fn lock<M: RawLock>(self) -> NumericBuilder<M> {
+        NumericBuilder {
+            capacity: self.capacity,
+            quantiles: self.quantiles,
+            buckets: self.buckets,
+            clock: self.clock,
+            smoothing: self.smoothing,
+            marker: PhantomData,
+        }
+    }
+    pub fn build(self) -> NumericBuffer<L> {
+        let clock = match (self.clock, &self.smoothing) {
+            #[cfg(feature = "std")]
+            (None, Some(Smoothing::HalfLife(_))) => {
+                Some(Box::new(SystemClock::new()) as Box<dyn Clock>)
+            }
+            (clock, _) => clock,
+        };
+        let mut window = Window::new(self.capacity, clock);
+        window.ema = self.smoothing.map(Ema::new);
+        window.order = self.quantiles.then(|| Order::new(self.capacity));
+        window.histogram = self.buckets.map(Histogram::new);
+        NumericBuffer {
+            window: Arc::new(Lock::new(window)),
+        }
+    }
+}
+
+impl NumericBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self::builder(capacity).build()
+    }
+    pub fn builder(capacity: usize) -> NumericBuilder {
+        NumericBuilder {
+            capacity,
+            quantiles: false,
+            buckets: None,
+            clock: None,
+            smoothing: None,
+            marker: PhantomData,
+        }
+    }
+}
+impl<L: RawLock> NumericBuffer<L> {
+    /// Like `new`, but guarded by the lock `L` instead of `DefaultLock`.
+    pub fn with_lock(capacity: usize) -> Self {
+        NumericBuffer::builder(capacity).lock().build()
+    }
+    pub fn len(&self) -> usize {
+        self.window.read().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn capacity(&self) -> usize {
+        self.window.read().values.capacity()
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        self.window.read().values.fill_level()
+    }
+    pub fn push(&self, value: f64) {
+        self.window.write().push(value)
+    }
+    pub fn push_slice(&self, slice: &[f64]) {
+        let mut window = self.window.write();
+        for value in slice {
+            window.push(*value);
+        }
+    }
+    pub fn clear(&self) {
+        self.window.write().clear()
+    }
+    pub fn snapshot(&self) -> Vec<f64> {
+        self.window.read().values.snapshot()
+    }
+    /// The sum of the values, 0 when empty.
+    pub fn sum(&self) -> f64 {
+        self.window.read().sum.get()
+    }
+    pub fn mean(&self) -> Option<f64> {
+        Some(self.summary()?.mean)
+    }
+    /// The population variance.
+    pub fn variance(&self) -> Option<f64> {
+        Some(self.summary()?.variance)
+    }
+    #[cfg(feature = "std")]
+    pub fn std_dev(&self) -> Option<f64> {
+        Some(self.variance()?.sqrt())
+    }
+    pub fn min(&self) -> Option<f64> {
+        Some(self.window.read().min.front()?.1)
+    }
+    pub fn max(&self) -> Option<f64> {
+        Some(self.window.read().max.front()?.1)
+    }
+    /// Every statistic at once, `None` when empty.
+    pub fn summary(&self) -> Option<Summary> {
+        self.window.read().summary()
+    }
+}
+
+impl<L: RawLock> Clone for NumericBuffer<L> {
+    fn clone(&self) -> Self {
+        Self {
+            window: self.window.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive(values: &[f64]) -> (f64, f64, f64, f64) {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / values.len() as f64;
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (mean, variance, min, max)
+    }
+
+    #[test]
+    fn rolling() {
+        let buffer = NumericBuffer::new(4);
+        assert_eq!(buffer.summary(), None);
+        assert_eq!(buffer.sum(), 0.0);
+
+        buffer.push_slice(&[3.0, 1.0, 4.0]);
+        assert_eq!(buffer.sum(), 8.0);
+        assert_eq!(buffer.min(), Some(1.0));
+        assert_eq!(buffer.max(), Some(4.0));
+
+        buffer.push_slice(&[1.0, 5.0, 9.0]);
+        assert_eq!(buffer.snapshot(), vec![4.0, 1.0, 5.0, 9.0]);
+        let summary = buffer.summary().unwrap();
+        assert_eq!(summary.len, 4);
+        assert_eq!(summary.sum, 19.0);
+        assert_eq!(summary.mean, 4.75);
+        assert!((summary.variance - 8.1875).abs() < 1e-12);
+        assert_eq!((summary.min, summary.max), (1.0, 9.0));
+
+        buffer.push_slice(&[2.0, 6.0]);
+        assert_eq!(buffer.min(), Some(2.0));
+        assert_eq!(buffer.max(), Some(9.0));
+        buffer.push_slice(&[5.0, 3.0]);
+        assert_eq!(buffer.max(), Some(6.0));
+
+        buffer.clear();
+        assert_eq!(buffer.mean(), None);
+        assert_eq!(buffer.sum(), 0.0);
+    }
+
+    #[test]
+    fn matches_naive() {
+        let buffer = NumericBuffer::new(16);
+        let mut values = Vec::new();
+        let mut seed = 7u64;
+        for _ in 0..1000 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let value = 1e9 + (seed >> 40) as f64 / 1e3;
+            buffer.push(value);
+            values.push(value);
+
+            let window = &values[values.len().saturating_sub(16)..];
+            let (mean, variance, min, max) = naive(window);
+            let summary = buffer.summary().unwrap();
+            assert!((summary.mean - mean).abs() < 1e-6);
+            assert!((summary.variance - variance).abs() < 1e-3 * variance.max(1.0));
+            assert_eq!((summary.min, summary.max), (min, max));
+        }
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let buffer = NumericBuffer::new(0);
+        buffer.push(1.0);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.max(), None);
+    }
+
+    #[test]
+    fn non_finite() {
+        let buffer = NumericBuffer::new(2);
+        buffer.push_slice(&[1.0, f64::INFINITY, f64::NAN, f64::NEG_INFINITY, 3.0]);
+        assert_eq!(buffer.snapshot(), vec![1.0, 3.0]);
+        assert_eq!(buffer.sum(), 4.0);
+        assert_eq!(buffer.mean(), Some(2.0));
+        assert_eq!(buffer.variance(), Some(1.0));
+    }
+}
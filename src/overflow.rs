@@ -0,0 +1,278 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use super::*;
+#[cfg(feature = "std")]
+use crate::wait::deadline;
+
+/// What `push` does once the buffer is full.
+///
+/// Non-exhaustive because the `std` feature adds `Block`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Overflow {
+    #[default]
+    OverwriteOldest,
+    RejectNew,
+    /// Waits for space, forever if `timeout` is `None`. A zero capacity buffer
+    /// never has space, so its pushes fail straight away.
+    #[cfg(feature = "std")]
+    Block {
+        timeout: Option<Duration>,
+    },
+}
+
+/// A value that could not be pushed, handed back to the caller.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PushError<T> {
+    Full(T),
+    Timeout(T),
+}
+
+impl<T> PushError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            PushError::Full(value) | PushError::Timeout(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Full(_) => f.write_str("Full(..)"),
+            PushError::Timeout(_) => f.write_str("Timeout(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Full(_) => f.write_str("buffer is full"),
+            PushError::Timeout(_) => f.write_str("timed out waiting for buffer space"),
+        }
+    }
+}
+
+impl<T> core::error::Error for PushError<T> {}
+
+#[cfg(feature = "alloc")]
+enum Attempt {
+    Merged,
+    Pushed,
+    Full,
+}
+
+#[cfg(feature = "alloc")]
+impl<T, L: RawLock> Shared<T, L> {
+    pub(crate) fn try_push(&self, value: T) -> Result<(), PushError<T>> {
+        self.push_or_merge(value, |_, _| false).map(drop)
+    }
+    /// Returns `Ok(true)` if `merge` folded the value into the head instead of pushing it.
+    pub(crate) fn push_or_merge(
+        &self,
+        value: T,
+        mut merge: impl FnMut(&mut T, &T) -> bool,
+    ) -> Result<bool, PushError<T>> {
+        #[cfg(feature = "std")]
+        if let Overflow::Block { timeout } = self.overflow {
+            return self.push_or_merge_until(value, merge, timeout.and_then(deadline));
+        }
+        let (mut value, mut evicted) = (Some(value), Vec::new());
+        let attempt =
+            self.update(|buffer| self.attempt(buffer, &mut value, &mut merge, &mut evicted));
+        self.finish(Some(attempt), value, evicted)
+    }
+    // `merge` runs under the write lock, and again each time a blocked push retries
+    #[cfg(feature = "std")]
+    fn push_or_merge_until(
+        &self,
+        value: T,
+        mut merge: impl FnMut(&mut T, &T) -> bool,
+        deadline: Option<Instant>,
+    ) -> Result<bool, PushError<T>> {
+        let (mut value, mut evicted) = (Some(value), Vec::new());
+        let attempt = self.wait_for(
+            || {
+                let mut buffer = self.buffer.write();
+                let attempt = self.attempt(&mut buffer, &mut value, &mut merge, &mut evicted);
+                buffer.observe();
+                match attempt {
+                    // nothing can ever make room in a zero capacity buffer
+                    Attempt::Full if buffer.capacity() > 0 => None,
+                    attempt => Some(attempt),
+                }
+            },
+            deadline,
+        );
+        if attempt.is_some() {
+            self.notify();
+        }
+        self.finish(attempt, value, evicted)
+    }
+    // takes `value` unless it is merged or the buffer is full, after dropping expired values
+    fn attempt(
+        &self,
+        buffer: &mut buffer::Buffer<T>,
+        value: &mut Option<T>,
+        merge: &mut impl FnMut(&mut T, &T) -> bool,
+        evicted: &mut Vec<T>,
+    ) -> Attempt {
+        self.expire_locked(buffer, evicted);
+        if let Some(head) = buffer.head_mut() {
+            if merge(head, value.as_ref().unwrap()) {
+                return Attempt::Merged;
+            }
+        }
+        if buffer.is_full() && self.overflow != Overflow::OverwriteOldest {
+            return Attempt::Full;
+        }
+        self.store(buffer, value.take().unwrap(), evicted);
+        Attempt::Pushed
+    }
+    // after the write lock is released; `None` means the attempt timed out
+    fn finish(
+        &self,
+        attempt: Option<Attempt>,
+        value: Option<T>,
+        evicted: Vec<T>,
+    ) -> Result<bool, PushError<T>> {
+        self.hooks.evict(evicted);
+        match attempt {
+            Some(Attempt::Merged) => Ok(true),
+            Some(Attempt::Pushed) => Ok(false),
+            Some(Attempt::Full) => Err(PushError::Full(value.unwrap())),
+            None => Err(PushError::Timeout(value.unwrap())),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone, L: RawLock> Shared<T, L> {
+    pub(crate) fn push_slice(&self, slice: &[T]) -> usize {
+        let mut evicted = Vec::new();
+        let accepted = match self.overflow {
+            Overflow::OverwriteOldest => self.update(|buffer| {
+                self.expire_locked(buffer, &mut evicted);
+                for value in slice {
+                    self.store(buffer, value.clone(), &mut evicted);
+                }
+                slice.len()
+            }),
+            Overflow::RejectNew => self.update(|buffer| {
+                self.expire_locked(buffer, &mut evicted);
+                let accepted = slice.len().min(buffer.capacity() - buffer.len());
+                for value in &slice[..accepted] {
+                    self.store(buffer, value.clone(), &mut evicted);
+                }
+                accepted
+            }),
+            #[cfg(feature = "std")]
+            Overflow::Block { timeout } => {
+                let deadline = timeout.and_then(deadline);
+                slice
+                    .iter()
+                    .take_while(|value| {
+                        self.push_or_merge_until((*value).clone(), |_, _| false, deadline)
+                            .is_ok()
+                    })
+                    .count()
+            }
+        };
+        self.hooks.evict(evicted);
+        accepted
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn reject_new() {
+        let buffer = Buffer::builder(2).overflow(Overflow::RejectNew).build();
+        assert_eq!(buffer.push_slice(&[1, 2, 3]), 2);
+        assert_eq!(buffer.try_push(4), Err(PushError::Full(4)));
+        assert_eq!(buffer.snapshot(), vec![1, 2]);
+
+        assert_eq!(buffer.pop_front(), Some(1));
+        assert_eq!(buffer.try_push(5), Ok(()));
+        assert_eq!(buffer.snapshot(), vec![2, 5]);
+    }
+
+    #[test]
+    fn block() {
+        let buffer = Buffer::builder(2)
+            .overflow(Overflow::Block { timeout: None })
+            .build();
+        let writer = buffer.writer();
+
+        let producer = thread::spawn(move || writer.push_slice(&[1, 2, 3, 4]));
+        let mut received = vec![];
+        while received.len() < 4 {
+            buffer.wait_until(|state| state.len > 0);
+            received.extend(buffer.pop_front());
+        }
+        assert_eq!(producer.join().unwrap(), 4);
+        assert_eq!(received, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn block_timeout() {
+        let buffer = Buffer::builder(1)
+            .overflow(Overflow::Block {
+                timeout: Some(Duration::from_millis(10)),
+            })
+            .build();
+        assert_eq!(buffer.try_push(1), Ok(()));
+        assert_eq!(buffer.try_push(2), Err(PushError::Timeout(2)));
+        assert_eq!(buffer.push_slice(&[3, 4]), 0);
+        assert_eq!(buffer.snapshot(), vec![1]);
+    }
+
+    #[test]
+    fn block_zero_capacity() {
+        let buffer = Buffer::builder(0)
+            .overflow(Overflow::Block { timeout: None })
+            .build();
+        assert_eq!(buffer.try_push(1), Err(PushError::Full(1)));
+        assert_eq!(buffer.push_slice(&[2, 3]), 0);
+
+        let buffer = Buffer::builder(1)
+            .overflow(Overflow::Block { timeout: None })
+            .build();
+        buffer.push(1);
+        let writer = buffer.writer();
+        let producer = thread::spawn(move || writer.try_push(2));
+        thread::sleep(Duration::from_millis(10));
+        // wakes the blocked producer, which then gives up
+        assert_eq!(buffer.resize(0), vec![1]);
+        assert_eq!(producer.join().unwrap(), Err(PushError::Full(2)));
+    }
+
+    #[test]
+    fn push_or_merge() {
+        let buffer = Buffer::builder(2).overflow(Overflow::RejectNew).build();
+        let merge = |head: &mut (char, u32), new: &(char, u32)| {
+            let merged = head.0 == new.0;
+            if merged {
+                head.1 += new.1;
+            }
+            merged
+        };
+
+        assert_eq!(buffer.push_or_merge(('a', 1), merge), Ok(false));
+        assert_eq!(buffer.push_or_merge(('a', 1), merge), Ok(true));
+        assert_eq!(buffer.push_or_merge(('b', 1), merge), Ok(false));
+        // merging still works once the buffer is full
+        assert_eq!(buffer.push_or_merge(('b', 1), merge), Ok(true));
+        assert_eq!(
+            buffer.push_or_merge(('c', 1), merge),
+            Err(PushError::Full(('c', 1)))
+        );
+        assert_eq!(buffer.snapshot(), vec![('a', 2), ('b', 2)]);
+    }
+}
@@ -0,0 +1,379 @@
+use super::*;
+use crate::numeric::{NumericBuilder, Window};
+
+// levels of the skip list; with a quarter of the nodes promoted to each next level, enough for
+// 4^16 values
+const LEVELS: usize = 16;
+const NIL: usize = usize::MAX;
+
+// the next node on one level, and how many positions ahead it is
+#[derive(Clone, Copy)]
+struct Link {
+    next: usize,
+    width: usize,
+}
+
+struct Node {
+    value: f64,
+    links: Vec<Link>,
+}
+
+// The window's values in ascending order, as an indexable skip list: every link knows how many
+// values it skips, so inserting, removing and finding the value at a rank are all O(log n)
+// expected. Equal values are interchangeable, so removing any one of them keeps it in sync.
+//
+// Node 0 is the head, which sits before the first value on every level. Removed nodes are
+// recycled, so a full window stops allocating.
+pub(crate) struct Order {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    len: usize,
+    // xorshift state for the node levels
+    random: u64,
+}
+
+impl Order {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let mut nodes = Vec::with_capacity(capacity + 1);
+        nodes.push(Node {
+            value: f64::NEG_INFINITY,
+            links: vec![
+                Link {
+                    next: NIL,
+                    width: 1
+                };
+                LEVELS
+            ],
+        });
+        Self {
+            nodes,
+            free: Vec::new(),
+            len: 0,
+            random: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+    fn level(&mut self) -> usize {
+        self.random ^= self.random << 13;
+        self.random ^= self.random >> 7;
+        self.random ^= self.random << 17;
+        (1 + self.random.trailing_zeros() as usize / 2).min(LEVELS)
+    }
+    // the last node before `value` on every level, and its position, the head being 0
+    fn predecessors(&self, value: f64) -> ([usize; LEVELS], [usize; LEVELS]) {
+        let (mut nodes, mut positions) = ([0; LEVELS], [0; LEVELS]);
+        let (mut node, mut position) = (0, 0);
+        for level in (0..LEVELS).rev() {
+            loop {
+                let link = self.nodes[node].links[level];
+                if link.next == NIL || self.nodes[link.next].value >= value {
+                    break;
+                }
+                position += link.width;
+                node = link.next;
+            }
+            nodes[level] = node;
+            positions[level] = position;
+        }
+        (nodes, positions)
+    }
+    pub(crate) fn insert(&mut self, value: f64) {
+        let (before, positions) = self.predecessors(value);
+        let height = self.level();
+        let node = match self.free.pop() {
+            Some(node) => node,
+            None => {
+                self.nodes.push(Node {
+                    value,
+                    links: Vec::new(),
+                });
+                self.nodes.len() - 1
+            }
+        };
+        let mut links = core::mem::take(&mut self.nodes[node].links);
+        links.clear();
+        // the new node lands right after `before[0]`
+        let position = positions[0] + 1;
+        for level in 0..LEVELS {
+            let link = &mut self.nodes[before[level]].links[level];
+            if level < height {
+                let skipped = position - positions[level];
+                links.push(Link {
+                    next: link.next,
+                    width: link.width + 1 - skipped,
+                });
+                *link = Link {
+                    next: node,
+                    width: skipped,
+                };
+            } else {
+                link.width += 1;
+            }
+        }
+        self.nodes[node] = Node { value, links };
+        self.len += 1;
+    }
+    // `value` must be one of the values
+    pub(crate) fn remove(&mut self, value: f64) {
+        let (before, _) = self.predecessors(value);
+        let node = self.nodes[before[0]].links[0].next;
+        debug_assert!(node != NIL && self.nodes[node].value == value);
+        for (level, before) in before.into_iter().enumerate() {
+            let removed = self.nodes[node].links.get(level).copied();
+            let link = &mut self.nodes[before].links[level];
+            match removed {
+                Some(removed) if link.next == node => {
+                    *link = Link {
+                        next: removed.next,
+                        width: link.width + removed.width - 1,
+                    };
+                }
+                _ => link.width -= 1,
+            }
+        }
+        self.free.push(node);
+        self.len -= 1;
+    }
+    pub(crate) fn clear(&mut self) {
+        self.nodes.truncate(1);
+        self.nodes[0].links.fill(Link {
+            next: NIL,
+            width: 1,
+        });
+        self.free.clear();
+        self.len = 0;
+    }
+    // the value at `rank`, 1 being the smallest
+    fn select(&self, rank: usize) -> Option<f64> {
+        if rank == 0 || rank > self.len {
+            return None;
+        }
+        let (mut node, mut position) = (0, 0);
+        for level in (0..LEVELS).rev() {
+            loop {
+                let link = self.nodes[node].links[level];
+                if link.next == NIL || position + link.width > rank {
+                    break;
+                }
+                position += link.width;
+                node = link.next;
+            }
+        }
+        Some(self.nodes[node].value)
+    }
+    // nearest rank: the smallest value with at least a fraction `q` of the values at or below it
+    fn quantile(&self, q: f64) -> Option<f64> {
+        let rank = q * self.len as f64;
+        let mut index = rank as usize;
+        if (index as f64) < rank {
+            index += 1;
+        }
+        self.select(index.max(1))
+    }
+}
+
+pub(crate) struct Histogram {
+    // upper bounds of every bucket but the last, which is unbounded
+    bounds: Box<[f64]>,
+    counts: Box<[usize]>,
+}
+
+impl Histogram {
+    pub(crate) fn new(bounds: Box<[f64]>) -> Self {
+        Self {
+            counts: vec![0; bounds.len() + 1].into_boxed_slice(),
+            bounds,
+        }
+    }
+    fn bucket(&self, value: f64) -> usize {
+        self.bounds.partition_point(|bound| *bound < value)
+    }
+    pub(crate) fn insert(&mut self, value: f64) {
+        self.counts[self.bucket(value)] += 1;
+    }
+    pub(crate) fn remove(&mut self, value: f64) {
+        self.counts[self.bucket(value)] -= 1;
+    }
+    pub(crate) fn clear(&mut self) {
+        self.counts.fill(0);
+    }
+    // interpolates linearly within the bucket holding the rank, which the exact minimum and
+    // maximum narrow down at both ends
+    fn quantile(&self, q: f64, min: f64, max: f64) -> f64 {
+        let rank = q * self.counts.iter().sum::<usize>() as f64;
+        let mut below = 0;
+        for (bucket, &count) in self.counts.iter().enumerate() {
+            if count > 0 && (below + count) as f64 >= rank {
+                let lower = match bucket {
+                    0 => min,
+                    bucket => self.bounds[bucket - 1].max(min),
+                };
+                let upper = self.bounds.get(bucket).map_or(max, |bound| bound.min(max));
+                let fraction = (rank - below as f64) / count as f64;
+                return lower + (upper - lower) * fraction.clamp(0.0, 1.0);
+            }
+            below += count;
+        }
+        max
+    }
+}
+
+impl<L: RawLock> NumericBuilder<L> {
+    /// Keeps the values in an indexable skip list for exact `quantile`s, at O(log capacity)
+    /// per push.
+    pub fn quantiles(mut self) -> Self {
+        self.quantiles = true;
+        self
+    }
+    /// Counts the values in fixed buckets for `histogram` and `approx_quantile`. Bucket `i`
+    /// holds the values up to `bounds[i]` and above the previous bound; a last bucket holds
+    /// everything above `bounds[bounds.len() - 1]`.
+    ///
+    /// Panics unless `bounds` is strictly increasing.
+    pub fn histogram(mut self, bounds: &[f64]) -> Self {
+        assert!(
+            bounds.windows(2).all(|pair| pair[0] < pair[1]),
+            "histogram bounds must be strictly increasing"
+        );
+        self.buckets = Some(bounds.into());
+        self
+    }
+}
+
+impl<L: RawLock> NumericBuffer<L> {
+    fn order<R>(&self, f: impl FnOnce(&Order) -> R) -> R {
+        let window = self.window.read();
+        let order = window.order.as_ref();
+        f(order.expect("quantiles are not enabled, see NumericBuilder::quantiles"))
+    }
+    fn histogram_of<R>(&self, f: impl FnOnce(&Window, &Histogram) -> R) -> R {
+        let window = self.window.read();
+        let histogram = window.histogram.as_ref();
+        f(
+            &window,
+            histogram.expect("no histogram, see NumericBuilder::histogram"),
+        )
+    }
+    /// The exact `q` quantile by nearest rank, so `quantile(0.5)` is the lower median.
+    ///
+    /// Panics unless built with `quantiles`, or if `q` is outside `0.0..=1.0`.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.quantiles([q]).map(|[value]| value)
+    }
+    /// Several quantiles from the same window, such as `quantiles([0.5, 0.95, 0.99])`.
+    pub fn quantiles<const N: usize>(&self, qs: [f64; N]) -> Option<[f64; N]> {
+        assert!(
+            qs.iter().all(|q| (0.0..=1.0).contains(q)),
+            "quantiles must be within 0.0..=1.0"
+        );
+        self.order(|order| {
+            let mut values = [0.0; N];
+            for (value, q) in values.iter_mut().zip(qs) {
+                *value = order.quantile(q)?;
+            }
+            Some(values)
+        })
+    }
+    /// The number of values in every bucket, one more than there are bounds.
+    ///
+    /// Panics unless built with `histogram`.
+    pub fn histogram(&self) -> Vec<usize> {
+        self.histogram_of(|_, histogram| histogram.counts.to_vec())
+    }
+    /// Estimates the `q` quantile from the histogram, in O(buckets).
+    ///
+    /// Panics unless built with `histogram`, or if `q` is outside `0.0..=1.0`.
+    pub fn approx_quantile(&self, q: f64) -> Option<f64> {
+        assert!(
+            (0.0..=1.0).contains(&q),
+            "quantiles must be within 0.0..=1.0"
+        );
+        self.histogram_of(|window, histogram| {
+            let min = window.min.front()?.1;
+            let max = window.max.front()?.1;
+            Some(histogram.quantile(q, min, max))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantiles() {
+        let buffer = NumericBuffer::builder(100).quantiles().build();
+        assert_eq!(buffer.quantile(0.5), None);
+
+        for value in (1..=250).rev() {
+            buffer.push(value as f64);
+        }
+        // the window holds 1 to 100
+        assert_eq!(
+            buffer.quantiles([0.0, 0.5, 0.95, 0.99, 1.0]),
+            Some([1.0, 50.0, 95.0, 99.0, 100.0])
+        );
+
+        buffer.push_slice(&[7.0; 50]);
+        assert_eq!(buffer.quantile(0.5), Some(7.0));
+        assert_eq!(buffer.quantile(0.575), Some(8.0));
+
+        buffer.clear();
+        assert_eq!(buffer.quantile(0.5), None);
+        buffer.push(3.0);
+        assert_eq!(buffer.quantiles([0.1, 0.9]), Some([3.0, 3.0]));
+    }
+
+    #[test]
+    fn matches_sorting() {
+        let buffer = NumericBuffer::builder(256).quantiles().build();
+        let mut random = 1u64;
+        for _ in 0..2000 {
+            random = random
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            // few distinct values, so duplicates are common
+            buffer.push((random >> 56) as f64);
+        }
+        let mut sorted = buffer.snapshot();
+        sorted.sort_by(f64::total_cmp);
+        // a power of two capacity keeps `q * len` exact
+        for rank in 1..=sorted.len() {
+            let q = rank as f64 / sorted.len() as f64;
+            assert_eq!(buffer.quantile(q), Some(sorted[rank - 1]));
+        }
+    }
+
+    #[test]
+    fn nan() {
+        let buffer = NumericBuffer::builder(2).quantiles().build();
+        buffer.push_slice(&[0.5, f64::NAN, 3.0]);
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.quantiles([0.0, 1.0]), Some([0.5, 3.0]));
+    }
+
+    #[test]
+    fn histogram() {
+        let buffer = NumericBuffer::builder(4)
+            .histogram(&[10.0, 20.0, 50.0])
+            .build();
+        assert_eq!(buffer.histogram(), vec![0, 0, 0, 0]);
+        assert_eq!(buffer.approx_quantile(0.5), None);
+
+        buffer.push_slice(&[5.0, 10.0, 15.0, 100.0]);
+        assert_eq!(buffer.histogram(), vec![2, 1, 0, 1]);
+        // overwrites move values out of their buckets
+        buffer.push_slice(&[30.0, 40.0]);
+        assert_eq!(buffer.histogram(), vec![0, 1, 2, 1]);
+
+        assert_eq!(buffer.approx_quantile(0.0), Some(15.0));
+        assert_eq!(buffer.approx_quantile(0.25), Some(20.0));
+        assert_eq!(buffer.approx_quantile(0.5), Some(35.0));
+        assert_eq!(buffer.approx_quantile(1.0), Some(100.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "quantiles are not enabled")]
+    fn not_enabled() {
+        NumericBuffer::new(4).quantile(0.5);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::numeric::{NumericBuilder, Window};
+
+use super::*;
+
+// how much a counter grew from `from` to `to`; a drop means it was reset and counted up from 0
+pub(crate) fn counter_delta(from: f64, to: f64) -> f64 {
+    if to >= from {
+        to - from
+    } else {
+        to
+    }
+}
+
+pub(crate) enum Smoothing {
+    Alpha(f64),
+    #[cfg(feature = "std")]
+    HalfLife(Duration),
+}
+
+impl Smoothing {
+    // the weight of a value pushed `elapsed` after the previous one
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    fn alpha(&self, elapsed: Duration) -> f64 {
+        match *self {
+            Smoothing::Alpha(alpha) => alpha,
+            #[cfg(feature = "std")]
+            Smoothing::HalfLife(half_life) => {
+                1.0 - 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+            }
+        }
+    }
+}
+
+pub(crate) struct Ema {
+    smoothing: Smoothing,
+    // the average and the time it was last updated
+    average: Option<(f64, Duration)>,
+}
+
+impl Ema {
+    pub(crate) fn new(smoothing: Smoothing) -> Self {
+        Self {
+            smoothing,
+            average: None,
+        }
+    }
+    pub(crate) fn push(&mut self, value: f64, time: Duration) {
+        let average = match self.average {
+            None => value,
+            Some((average, last)) => {
+                let alpha = self.smoothing.alpha(time.saturating_sub(last));
+                average + alpha * (value - average)
+            }
+        };
+        self.average = Some((average, time));
+    }
+    pub(crate) fn clear(&mut self) {
+        self.average = None;
+    }
+}
+
+impl Window {
+    // time between the oldest and the newest value, `None` without a clock
+    fn span(&self) -> Option<Duration> {
+        let newest = self.values.time(self.len().checked_sub(1)?)?;
+        Some(newest - self.values.time(0)?)
+    }
+}
+
+impl<L: RawLock> NumericBuilder<L> {
+    /// Keeps an exponential moving average of every value pushed, moving it by `alpha` of the
+    /// distance to each new value. Unlike the other statistics it is not limited to the window.
+    ///
+    /// Panics unless `alpha` is within `0.0..=1.0`.
+    pub fn ema(mut self, alpha: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&alpha),
+            "alpha must be within 0.0..=1.0"
+        );
+        self.smoothing = Some(Smoothing::Alpha(alpha));
+        self
+    }
+    /// Like `ema`, but weighted by time, so the weight of a value halves every `half_life`
+    /// after it was pushed. Uses a `SystemClock` unless a `clock` is set.
+    #[cfg(feature = "std")]
+    pub fn ema_half_life(mut self, half_life: Duration) -> Self {
+        assert!(!half_life.is_zero(), "half_life must not be zero");
+        self.smoothing = Some(Smoothing::HalfLife(half_life));
+        self
+    }
+    /// Timestamps every pushed value with `clock`, for `rate` and `events_per_second`.
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+}
+
+impl<L: RawLock> NumericBuffer<L> {
+    /// The exponential moving average, `None` until a value is pushed.
+    ///
+    /// Panics unless built with `ema` or `ema_half_life`.
+    pub fn ema(&self) -> Option<f64> {
+        let window = self.window.read();
+        let ema = window.ema.as_ref();
+        Some(
+            ema.expect("no moving average, see NumericBuilder::ema")
+                .average?
+                .0,
+        )
+    }
+    /// How much the values grew across the window when read as a counter, like Prometheus'
+    /// `increase` without extrapolation. A value lower than the one before counts as a reset.
+    pub fn increase(&self) -> f64 {
+        self.window.read().increase.get()
+    }
+    /// `increase` per second between the oldest and the newest value. Needs a `clock`.
+    pub fn rate(&self) -> Option<f64> {
+        let window = self.window.read();
+        let span = window.span().filter(|span| !span.is_zero())?;
+        Some(window.increase.get() / span.as_secs_f64())
+    }
+    /// How many values were pushed per second, from the timestamps of the oldest and the newest
+    /// value. Needs a `clock`.
+    pub fn events_per_second(&self) -> Option<f64> {
+        let window = self.window.read();
+        let span = window.span().filter(|span| !span.is_zero())?;
+        Some((window.len() - 1) as f64 / span.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ema() {
+        let buffer = NumericBuffer::builder(2).ema(0.5).build();
+        assert_eq!(buffer.ema(), None);
+        buffer.push_slice(&[8.0, 4.0, 0.0]);
+        assert_eq!(buffer.ema(), Some(3.0));
+        buffer.clear();
+        assert_eq!(buffer.ema(), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ema_half_life() {
+        let clock = ManualClock::new();
+        let buffer = NumericBuffer::builder(2)
+            .ema_half_life(Duration::from_secs(10))
+            .clock(clock.clone())
+            .build();
+        buffer.push(0.0);
+        clock.advance(Duration::from_secs(10));
+        buffer.push(8.0);
+        assert_eq!(buffer.ema(), Some(4.0));
+        // no time has passed, so the new value carries no weight
+        buffer.push(100.0);
+        assert_eq!(buffer.ema(), Some(4.0));
+    }
+
+    #[test]
+    fn rate() {
+        let clock = ManualClock::new();
+        let buffer = NumericBuffer::builder(4).clock(clock.clone()).build();
+        assert_eq!(buffer.rate(), None);
+
+        for value in [10.0, 20.0, 5.0, 15.0, 30.0] {
+            buffer.push(value);
+            clock.advance(Duration::from_secs(2));
+        }
+        // the window holds 20, 5 (a reset), 15 and 30, pushed over 6 seconds
+        assert_eq!(buffer.increase(), 30.0);
+        assert_eq!(buffer.rate(), Some(5.0));
+        assert_eq!(buffer.events_per_second(), Some(0.5));
+
+        let untimed = NumericBuffer::new(4);
+        untimed.push_slice(&[1.0, 3.0]);
+        assert_eq!(untimed.increase(), 2.0);
+        assert_eq!(untimed.rate(), None);
+    }
+}
@@ -0,0 +1,359 @@
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::*;
+
+#[repr(align(64))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+struct Ring<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    // read position modulo 2 * capacity, only written by the consumer
+    head: CachePadded<AtomicUsize>,
+    // write position modulo 2 * capacity, only written by the producer
+    tail: CachePadded<AtomicUsize>,
+}
+
+// SAFETY: a slot is only accessed by the side that currently owns it according to head and tail
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+    fn len(&self) -> usize {
+        // head first: it never passes tail, so the difference cannot underflow
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        self.distance(head, tail).min(self.capacity())
+    }
+    // positions run over 0..2 * capacity so that a full ring is distinguishable from an
+    // empty one, and both sides map every position to the same slot
+    fn advance(&self, index: usize, count: usize) -> usize {
+        let to_end = 2 * self.capacity() - index;
+        if count >= to_end {
+            count - to_end
+        } else {
+            index + count
+        }
+    }
+    fn distance(&self, from: usize, to: usize) -> usize {
+        if from <= to {
+            to - from
+        } else {
+            2 * self.capacity() - from + to
+        }
+    }
+    fn index(&self, index: usize) -> usize {
+        if index >= self.capacity() {
+            index - self.capacity()
+        } else {
+            index
+        }
+    }
+    fn fill_level(&self) -> FillLevel {
+        FillLevel::new(self.len(), self.capacity())
+    }
+    fn slot(&self, index: usize) -> *mut T {
+        UnsafeCell::raw_get(&self.slots[self.index(index)]).cast()
+    }
+    fn slots(&self) -> *mut T {
+        UnsafeCell::raw_get(self.slots.as_ptr()).cast()
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        let (head, tail) = (*self.head.0.get_mut(), *self.tail.0.get_mut());
+        let mut index = head;
+        while index != tail {
+            // SAFETY: the slots between head and tail are initialized
+            unsafe { ptr::drop_in_place(self.slot(index)) };
+            index = self.advance(index, 1);
+        }
+    }
+}
+
+/// A lock-free ring for exactly one producer and one consumer thread.
+///
+/// Both halves are wait-free. A full ring rejects new values instead of overwriting the
+/// oldest, since only the consumer may advance the read position.
+pub struct SpscBuffer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> SpscBuffer<T> {
+    /// Panics if `capacity` exceeds `usize::MAX / 2`.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity <= usize::MAX / 2, "capacity overflow");
+        let slots = (0..capacity)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+        Self {
+            ring: Arc::new(Ring {
+                slots,
+                head: CachePadded(AtomicUsize::new(0)),
+                tail: CachePadded(AtomicUsize::new(0)),
+            }),
+        }
+    }
+    pub fn split(self) -> (Producer<T>, Consumer<T>) {
+        let producer = Producer {
+            ring: self.ring.clone(),
+            head: 0,
+            tail: 0,
+        };
+        let consumer = Consumer {
+            ring: self.ring,
+            head: 0,
+            tail: 0,
+        };
+        (producer, consumer)
+    }
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        self.ring.fill_level()
+    }
+}
+
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+    // last head seen, refreshed only when the ring looks full
+    head: usize,
+    tail: usize,
+}
+
+impl<T> Producer<T> {
+    fn free(&mut self, wanted: usize) -> usize {
+        let capacity = self.ring.capacity();
+        let mut free = capacity - self.ring.distance(self.head, self.tail);
+        if free < wanted {
+            self.head = self.ring.head.load(Ordering::Acquire);
+            free = capacity - self.ring.distance(self.head, self.tail);
+        }
+        free
+    }
+    pub fn push(&mut self, value: T) -> Result<(), PushError<T>> {
+        if self.free(1) == 0 {
+            return Err(PushError::Full(value));
+        }
+        // SAFETY: the slot at tail is free and owned by the producer until tail is published
+        unsafe { self.ring.slot(self.tail).write(value) };
+        self.tail = self.ring.advance(self.tail, 1);
+        self.ring.tail.store(self.tail, Ordering::Release);
+        Ok(())
+    }
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        self.ring.fill_level()
+    }
+}
+impl<T: Copy> Producer<T> {
+    /// Copies as many values as fit and returns how many were pushed.
+    pub fn push_slice(&mut self, slice: &[T]) -> usize {
+        let count = self.free(slice.len()).min(slice.len());
+        if count == 0 {
+            return 0;
+        }
+        let start = self.ring.index(self.tail);
+        let first = count.min(self.ring.capacity() - start);
+        // SAFETY: the `count` slots after tail are free and owned by the producer
+        unsafe {
+            let slots = self.ring.slots();
+            ptr::copy_nonoverlapping(slice.as_ptr(), slots.add(start), first);
+            ptr::copy_nonoverlapping(slice.as_ptr().add(first), slots, count - first);
+        }
+        self.tail = self.ring.advance(self.tail, count);
+        self.ring.tail.store(self.tail, Ordering::Release);
+        count
+    }
+}
+
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+    head: usize,
+    // last tail seen, refreshed only when the ring looks empty
+    tail: usize,
+}
+
+impl<T> Consumer<T> {
+    fn available(&mut self, wanted: usize) -> usize {
+        let mut available = self.ring.distance(self.head, self.tail);
+        if available < wanted {
+            self.tail = self.ring.tail.load(Ordering::Acquire);
+            available = self.ring.distance(self.head, self.tail);
+        }
+        available
+    }
+    pub fn pop(&mut self) -> Option<T> {
+        if self.available(1) == 0 {
+            return None;
+        }
+        // SAFETY: the slot at head was published by the producer and is owned by the consumer
+        let value = unsafe { self.ring.slot(self.head).read() };
+        self.head = self.ring.advance(self.head, 1);
+        self.ring.head.store(self.head, Ordering::Release);
+        Some(value)
+    }
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity()
+    }
+    pub fn fill_level(&self) -> FillLevel {
+        self.ring.fill_level()
+    }
+}
+impl<T: Copy> Consumer<T> {
+    /// Copies as many values as are available into `out` and returns how many were popped.
+    pub fn pop_slice(&mut self, out: &mut [T]) -> usize {
+        let count = self.available(out.len()).min(out.len());
+        if count == 0 {
+            return 0;
+        }
+        let start = self.ring.index(self.head);
+        let first = count.min(self.ring.capacity() - start);
+        // SAFETY: the `count` slots after head were published and are owned by the consumer
+        unsafe {
+            let slots = self.ring.slots();
+            ptr::copy_nonoverlapping(slots.add(start), out.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(slots, out.as_mut_ptr().add(first), count - first);
+        }
+        self.head = self.ring.advance(self.head, count);
+        self.ring.head.store(self.head, Ordering::Release);
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn push_pop() {
+        let buffer = SpscBuffer::new(2);
+        assert_eq!(buffer.fill_level(), FillLevel::Empty);
+        let (mut producer, mut consumer) = buffer.split();
+
+        assert_eq!(producer.push(1), Ok(()));
+        assert_eq!(producer.fill_level(), FillLevel::Partial);
+        assert_eq!(producer.push(2), Ok(()));
+        assert_eq!(producer.push(3), Err(PushError::Full(3)));
+        assert_eq!(consumer.fill_level(), FillLevel::Full);
+
+        assert_eq!(consumer.pop(), Some(1));
+        assert_eq!(producer.push(3), Ok(()));
+        assert_eq!(consumer.pop(), Some(2));
+        assert_eq!(consumer.pop(), Some(3));
+        assert_eq!(consumer.pop(), None);
+        assert!(producer.is_empty());
+    }
+
+    #[test]
+    fn slices() {
+        let (mut producer, mut consumer) = SpscBuffer::new(4).split();
+        let mut out = [0; 4];
+
+        assert_eq!(producer.push_slice(&[1, 2, 3]), 3);
+        assert_eq!(consumer.pop_slice(&mut out[..2]), 2);
+        assert_eq!(out[..2], [1, 2]);
+
+        // wraps around the end of the ring
+        assert_eq!(producer.push_slice(&[4, 5, 6, 7]), 3);
+        assert_eq!(producer.len(), 4);
+        assert_eq!(consumer.pop_slice(&mut out), 4);
+        assert_eq!(out, [3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn wraps_positions() {
+        // positions wrap at 2 * capacity, several times over
+        let (mut producer, mut consumer) = SpscBuffer::new(3).split();
+        let mut out = [0; 2];
+        for round in 0..10 {
+            assert_eq!(producer.push_slice(&[round, round + 1]), 2);
+            assert_eq!(producer.push(round + 2), Ok(()));
+            assert_eq!(producer.push(0), Err(PushError::Full(0)));
+            assert_eq!(consumer.pop(), Some(round));
+            assert_eq!(consumer.pop_slice(&mut out), 2);
+            assert_eq!(out, [round + 1, round + 2]);
+            assert!(consumer.is_empty());
+        }
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let (mut producer, mut consumer) = SpscBuffer::new(0).split();
+        assert_eq!(producer.push(1), Err(PushError::Full(1)));
+        assert_eq!(producer.push_slice(&[1]), 0);
+        assert_eq!(consumer.pop(), None);
+    }
+
+    #[test]
+    fn drops() {
+        let value = Arc::new(());
+        let (mut producer, consumer) = SpscBuffer::new(3).split();
+        producer.push(value.clone()).unwrap();
+        producer.push(value.clone()).unwrap();
+        drop(producer);
+        drop(consumer);
+        assert_eq!(Arc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn threads() {
+        let (mut producer, mut consumer) = SpscBuffer::new(16).split();
+        let handle = thread::spawn(move || {
+            for value in 0..10_000u32 {
+                while producer.push(value).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < 10_000 {
+            match consumer.pop() {
+                Some(value) => {
+                    assert_eq!(value, expected);
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+    }
+}
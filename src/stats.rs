@@ -0,0 +1,213 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use super::*;
+
+/// Lifetime totals of a buffer, since it was built or `reset_stats` was last called.
+///
+/// Non-exhaustive because the `std` feature adds `time_full`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// Values accepted by a push, including those a zero capacity buffer discards at once.
+    pub pushes: u64,
+    /// Values dropped to make room for a push because the buffer was full.
+    pub overwritten: u64,
+    pub clears: u64,
+    /// Values removed by `pop_front`, `pop_back`, `pop_n`, `drain` and `resize`.
+    pub pops: u64,
+    /// Values dropped for being older than `max_age`.
+    pub expired: u64,
+    /// The highest `len` reached.
+    pub high_water_mark: usize,
+    /// Total time spent at `FillLevel::Full`.
+    #[cfg(feature = "std")]
+    pub time_full: Duration,
+}
+
+// Kept inside `buffer::Buffer`, so they are only written under its write lock and need no
+// atomics, which 32-bit targets lack for 64-bit counters.
+pub(crate) struct Counters {
+    pushes: u64,
+    overwritten: u64,
+    clears: u64,
+    pops: u64,
+    expired: u64,
+    high_water_mark: usize,
+    #[cfg(feature = "std")]
+    epoch: Instant,
+    // nanoseconds since `epoch` when the buffer became full, while it still is
+    #[cfg(feature = "std")]
+    full_since: Option<u64>,
+    // nanoseconds spent full before `full_since`
+    #[cfg(feature = "std")]
+    time_full: u64,
+}
+
+impl Counters {
+    pub(crate) fn new() -> Self {
+        Self {
+            pushes: 0,
+            overwritten: 0,
+            clears: 0,
+            pops: 0,
+            expired: 0,
+            high_water_mark: 0,
+            #[cfg(feature = "std")]
+            epoch: Instant::now(),
+            #[cfg(feature = "std")]
+            full_since: None,
+            #[cfg(feature = "std")]
+            time_full: 0,
+        }
+    }
+    pub(crate) fn push(&mut self, overwrote: bool) {
+        self.pushes += 1;
+        if overwrote {
+            self.overwritten += 1;
+        }
+    }
+    #[cfg(feature = "std")]
+    pub(crate) fn push_n(&mut self, pushed: usize, overwritten: usize) {
+        self.pushes += pushed as u64;
+        self.overwritten += overwritten as u64;
+    }
+    pub(crate) fn pop(&mut self, popped: usize) {
+        self.pops += popped as u64;
+    }
+    pub(crate) fn expire(&mut self, expired: usize) {
+        self.expired += expired as u64;
+    }
+    pub(crate) fn clear(&mut self) {
+        self.clears += 1;
+    }
+    // after every change to the buffer
+    fn observe_len(&mut self, len: usize) {
+        self.high_water_mark = self.high_water_mark.max(len);
+    }
+    #[cfg(feature = "std")]
+    fn observe_full(&mut self, full: bool) {
+        match (full, self.full_since) {
+            (true, None) => self.full_since = Some(self.now()),
+            (false, Some(since)) => {
+                self.time_full += self.now() - since;
+                self.full_since = None;
+            }
+            _ => {}
+        }
+    }
+    #[cfg(feature = "std")]
+    fn now(&self) -> u64 {
+        self.epoch.elapsed().as_nanos() as u64
+    }
+    pub(crate) fn get(&self) -> Stats {
+        Stats {
+            pushes: self.pushes,
+            overwritten: self.overwritten,
+            clears: self.clears,
+            pops: self.pops,
+            expired: self.expired,
+            high_water_mark: self.high_water_mark,
+            #[cfg(feature = "std")]
+            time_full: {
+                let current = self.full_since.map_or(0, |since| self.now() - since);
+                Duration::from_nanos(self.time_full + current)
+            },
+        }
+    }
+}
+
+impl<T> buffer::Buffer<T> {
+    pub(crate) fn observe(&mut self) {
+        let len = self.len();
+        self.stats.observe_len(len);
+        #[cfg(feature = "std")]
+        {
+            let full = self.is_full() && self.capacity() > 0;
+            self.stats.observe_full(full);
+        }
+    }
+    // starts counting from zero, with the current `len` as the high-water mark
+    fn reset_stats(&mut self) {
+        self.stats = Counters::new();
+        self.observe();
+    }
+}
+
+impl<T, L: RawLock> Buffer<T, L> {
+    pub fn stats(&self) -> Stats {
+        self.shared.buffer.read().stats.get()
+    }
+    /// Starts counting from zero, with the current `len` as the high-water mark.
+    pub fn reset_stats(&self) {
+        self.shared.buffer.write().reset_stats()
+    }
+}
+
+impl<T, L: RawLock> Reader<T, L> {
+    pub fn stats(&self) -> Stats {
+        self.shared.buffer.read().stats.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let buffer = Buffer::new(3);
+        buffer.push_slice(&[1, 2, 3, 4]);
+        buffer.push(5);
+        assert_eq!(buffer.pop_n(2), vec![3, 4]);
+        assert_eq!(buffer.pop_back(), Some(5));
+        assert_eq!(buffer.pop_front(), None);
+        buffer.push(6);
+        buffer.clear();
+
+        let stats = buffer.stats();
+        assert_eq!(stats.pushes, 6);
+        assert_eq!(stats.overwritten, 2);
+        assert_eq!(stats.pops, 3);
+        assert_eq!(stats.clears, 1);
+        assert_eq!(stats.high_water_mark, 3);
+        assert_eq!(buffer.reader().stats(), stats);
+
+        buffer.push_slice(&[7, 8]);
+        assert_eq!(buffer.resize(0), vec![7, 8]);
+        buffer.push(9);
+        let stats = buffer.stats();
+        assert_eq!(stats.pushes, 9);
+        assert_eq!(stats.overwritten, 2);
+        assert_eq!(stats.pops, 5);
+
+        buffer.resize(3);
+        buffer.push(7);
+        buffer.reset_stats();
+        assert_eq!(
+            buffer.stats(),
+            Stats {
+                high_water_mark: 1,
+                ..Stats::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn time_full() {
+        let buffer = Buffer::new(1);
+        assert_eq!(buffer.stats().time_full, Duration::ZERO);
+
+        buffer.push(1);
+        std::thread::sleep(Duration::from_millis(5));
+        let full = buffer.stats().time_full;
+        assert!(full >= Duration::from_millis(5));
+
+        buffer.pop_front();
+        let stopped = buffer.stats().time_full;
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(buffer.stats().time_full, stopped);
+        assert!(stopped >= full);
+    }
+}
@@ -0,0 +1,212 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<T> {
+    Push(T),
+    /// The buffer was cleared; values yielded before no longer reflect its contents.
+    Clear,
+}
+
+impl<T, L: RawLock> Shared<T, L> {
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.write();
+        if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+            wakers.push(waker.clone());
+            self.waiting.fetch_add(1, Ordering::Relaxed);
+        }
+        drop(wakers);
+        // pairs with the fence in `notify`, before the caller checks for a change again
+        fence(Ordering::SeqCst);
+    }
+}
+
+/// Resolves once a value has been pushed after this future was created.
+pub struct NextPush<'a, T, L: RawLock = DefaultLock> {
+    shared: &'a Shared<T, L>,
+    pushed: u64,
+}
+
+impl<'a, T, L: RawLock> NextPush<'a, T, L> {
+    fn new(shared: &'a Shared<T, L>) -> Self {
+        let pushed = shared.read().pushed();
+        Self { shared, pushed }
+    }
+    fn ready(&self) -> Option<State> {
+        let state = self.shared.read().state();
+        (state.pushed > self.pushed).then_some(state)
+    }
+}
+
+impl<T, L: RawLock> Future for NextPush<'_, T, L> {
+    type Output = State;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<State> {
+        if let Some(state) = self.ready() {
+            return Poll::Ready(state);
+        }
+        self.shared.register(cx.waker());
+        match self.ready() {
+            Some(state) => Poll::Ready(state),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Yields every value pushed after the stream was created, and `Event::Clear` on `clear`.
+///
+/// Values that are overwritten or popped before the stream gets to them are skipped. The
+/// stream ends once all writers are gone and every remaining value has been yielded.
+/// `poll_next` follows the signature of the `futures` `Stream` trait.
+pub struct PushStream<T, L: RawLock = DefaultLock> {
+    reader: Reader<T, L>,
+    // sequence number of the next value to yield
+    seen: u64,
+    cleared: u64,
+}
+
+impl<T: Clone, L: RawLock> PushStream<T, L> {
+    fn new(reader: Reader<T, L>) -> Self {
+        let (seen, cleared) = {
+            let buffer = reader.shared.read();
+            (buffer.pushed(), buffer.cleared())
+        };
+        Self {
+            reader,
+            seen,
+            cleared,
+        }
+    }
+    fn try_next(&mut self) -> Option<Event<T>> {
+        let buffer = self.reader.shared.read();
+        if buffer.cleared() != self.cleared {
+            self.cleared = buffer.cleared();
+            return Some(Event::Clear);
+        }
+        let index = buffer.position(self.seen);
+        let value = buffer.get(index)?.clone();
+        self.seen = buffer.seq(index)? + 1;
+        Some(Event::Push(value))
+    }
+    pub fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event<T>>> {
+        let this = self.get_mut();
+        if let Some(event) = this.try_next() {
+            return Poll::Ready(Some(event));
+        }
+        this.reader.shared.register(cx.waker());
+        let closed = this.reader.is_closed();
+        match this.try_next() {
+            Some(event) => Poll::Ready(Some(event)),
+            None if closed => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+    pub fn next_event(&mut self) -> NextEvent<'_, T, L> {
+        NextEvent { stream: self }
+    }
+}
+
+pub struct NextEvent<'a, T, L: RawLock = DefaultLock> {
+    stream: &'a mut PushStream<T, L>,
+}
+
+impl<T: Clone, L: RawLock> Future for NextEvent<'_, T, L> {
+    type Output = Option<Event<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.get_mut().stream).poll_next(cx)
+    }
+}
+
+impl<T, L: RawLock> Buffer<T, L> {
+    pub fn next_push(&self) -> NextPush<'_, T, L> {
+        NextPush::new(&self.shared)
+    }
+}
+impl<T: Clone, L: RawLock> Buffer<T, L> {
+    pub fn stream(&self) -> PushStream<T, L> {
+        PushStream::new(self.reader())
+    }
+}
+
+impl<T, L: RawLock> Reader<T, L> {
+    pub fn next_push(&self) -> NextPush<'_, T, L> {
+        NextPush::new(&self.shared)
+    }
+}
+impl<T: Clone, L: RawLock> Reader<T, L> {
+    pub fn stream(&self) -> PushStream<T, L> {
+        PushStream::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+
+    use super::*;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn next_push() {
+        let buffer = Buffer::new(2);
+        let writer = buffer.writer();
+        let next = buffer.next_push();
+
+        let producer = thread::spawn(move || writer.push(1));
+        assert_eq!(block_on(next).pushed, 1);
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn stream() {
+        let buffer = Buffer::new(4);
+        buffer.push(0);
+        let mut stream = buffer.stream();
+        let writer = buffer.writer();
+        drop(buffer);
+
+        let producer = thread::spawn(move || {
+            writer.push(1);
+            writer.push_slice(&[2, 3]);
+            writer.clear();
+            writer.push(4);
+        });
+        let mut events = vec![];
+        while let Some(event) = block_on(stream.next_event()) {
+            events.push(event);
+        }
+        producer.join().unwrap();
+
+        // values still unseen when the clear happens are skipped
+        assert_eq!(events.last(), Some(&Event::Push(4)));
+        let clear = events.iter().position(|event| *event == Event::Clear);
+        assert!([1, 2, 3]
+            .map(Event::Push)
+            .starts_with(&events[..clear.unwrap()]));
+    }
+}
@@ -0,0 +1,250 @@
+use core::iter::FusedIterator;
+use core::ops::{Bound, Range, RangeBounds};
+#[cfg(feature = "std")]
+use core::sync::atomic::AtomicU64;
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::*;
+use crate::clock::Live;
+
+/// Time since the Unix epoch, for timestamps that line up with external logs.
+///
+/// Held back instead of going backwards when the system time is adjusted.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct WallClock {
+    // latest time handed out, in nanoseconds
+    last: AtomicU64,
+}
+
+#[cfg(feature = "std")]
+impl WallClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for WallClock {
+    fn now(&self) -> Duration {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        let last = self.last.fetch_max(now, Ordering::Relaxed);
+        Duration::from_nanos(now.max(last))
+    }
+}
+
+impl<T> buffer::Buffer<T> {
+    pub(crate) fn time(&self, index: usize) -> Option<Duration> {
+        self.get(index)?;
+        let (older, newer) = self.time_slices()?;
+        older
+            .get(index)
+            .or_else(|| newer.get(index - older.len()))
+            .copied()
+    }
+}
+
+impl<T, L: RawLock> Live<'_, T, L> {
+    // number of the oldest elements whose timestamp satisfies `f`, which must hold for a prefix;
+    // 0 without timestamps
+    fn count_times(&self, f: impl Fn(Duration) -> bool) -> usize {
+        let Some((older, newer)) = self.time_slices() else {
+            return 0;
+        };
+        match older.last() {
+            Some(last) if !f(*last) => older.partition_point(|time| f(*time)),
+            _ => older.len() + newer.partition_point(|time| f(*time)),
+        }
+    }
+    fn time(&self, index: usize) -> Option<Duration> {
+        self.buffer.time(index.checked_add(self.skip)?)
+    }
+    // `None` without timestamps
+    fn timed(&self, range: Range<usize>) -> Option<TimedIter<'_, T>> {
+        let times = self.time_slices()?;
+        let (older, newer) = self.range(range.clone()).unwrap();
+        let (older_times, newer_times) = buffer::sub_slices(times, range);
+        Some(TimedIter {
+            values: Iter::new(older, newer),
+            times: Iter::new(older_times, newer_times),
+        })
+    }
+    // logical indices of the elements with a timestamp in `range`
+    fn time_range(&self, range: impl RangeBounds<Duration>) -> Range<usize> {
+        let start = match range.start_bound() {
+            Bound::Included(&from) => self.count_times(|time| time < from),
+            Bound::Excluded(&from) => self.count_times(|time| time <= from),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&to) => self.count_times(|time| time <= to),
+            Bound::Excluded(&to) => self.count_times(|time| time < to),
+            Bound::Unbounded => self.count_times(|_| true),
+        };
+        start..end.max(start)
+    }
+}
+
+/// Iterates over `(timestamp, &value)` pairs, oldest first.
+pub struct TimedIter<'a, T> {
+    values: Iter<'a, T>,
+    times: Iter<'a, Duration>,
+}
+
+impl<'a, T> Iterator for TimedIter<'a, T> {
+    type Item = (Duration, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((*self.times.next()?, self.values.next()?))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for TimedIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some((*self.times.next_back()?, self.values.next_back()?))
+    }
+}
+
+impl<T> ExactSizeIterator for TimedIter<'_, T> {}
+
+impl<T> FusedIterator for TimedIter<'_, T> {}
+
+impl<T, L: RawLock> ReadGuard<'_, T, L> {
+    /// `None` for buffers without a `clock`.
+    pub fn iter_with_time(&self) -> Option<TimedIter<'_, T>> {
+        self.buffer.timed(0..self.len())
+    }
+    /// Iterates over the values pushed within a time range, found by binary search. `None` for
+    /// buffers without a `clock`.
+    pub fn range_by_time(&self, range: impl RangeBounds<Duration>) -> Option<TimedIter<'_, T>> {
+        self.buffer.timed(self.buffer.time_range(range))
+    }
+    pub fn oldest_time(&self) -> Option<Duration> {
+        self.buffer.time(0)
+    }
+    pub fn newest_time(&self) -> Option<Duration> {
+        self.buffer.time(self.len().checked_sub(1)?)
+    }
+}
+
+impl<T, L: RawLock> Buffer<T, L> {
+    /// Push time of the oldest value, from the buffer's `clock`. `None` if the buffer is empty or
+    /// has no clock.
+    pub fn oldest_time(&self) -> Option<Duration> {
+        self.read().oldest_time()
+    }
+    pub fn newest_time(&self) -> Option<Duration> {
+        self.read().newest_time()
+    }
+}
+impl<T: Clone, L: RawLock> Buffer<T, L> {
+    /// Like `snapshot`, with the push time of every value. `None` if the buffer has no clock.
+    pub fn snapshot_with_time(&self) -> Option<Vec<(Duration, T)>> {
+        let guard = self.read();
+        let values = guard.iter_with_time()?;
+        Some(values.map(|(time, value)| (time, value.clone())).collect())
+    }
+    /// The values pushed within a time range, oldest first. `None` if the buffer has no clock.
+    pub fn range_by_time(&self, range: impl RangeBounds<Duration>) -> Option<Vec<(Duration, T)>> {
+        let guard = self.read();
+        let values = guard.range_by_time(range)?;
+        Some(values.map(|(time, value)| (time, value.clone())).collect())
+    }
+}
+
+impl<T, L: RawLock> Reader<T, L> {
+    pub fn oldest_time(&self) -> Option<Duration> {
+        self.read().oldest_time()
+    }
+    pub fn newest_time(&self) -> Option<Duration> {
+        self.read().newest_time()
+    }
+}
+impl<T: Clone, L: RawLock> Reader<T, L> {
+    pub fn snapshot_with_time(&self) -> Option<Vec<(Duration, T)>> {
+        let guard = self.read();
+        let values = guard.iter_with_time()?;
+        Some(values.map(|(time, value)| (time, value.clone())).collect())
+    }
+    pub fn range_by_time(&self, range: impl RangeBounds<Duration>) -> Option<Vec<(Duration, T)>> {
+        let guard = self.read();
+        let values = guard.range_by_time(range)?;
+        Some(values.map(|(time, value)| (time, value.clone())).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn range_by_time() {
+        let clock = ManualClock::new();
+        let buffer = Buffer::builder(4).clock(clock.clone()).build();
+        assert_eq!(buffer.oldest_time(), None);
+
+        for value in 0..6 {
+            clock.set(secs(value * 10));
+            buffer.push(value);
+        }
+        assert_eq!(buffer.oldest_time(), Some(secs(20)));
+        assert_eq!(buffer.newest_time(), Some(secs(50)));
+        assert_eq!(
+            buffer.snapshot_with_time(),
+            Some(vec![
+                (secs(20), 2),
+                (secs(30), 3),
+                (secs(40), 4),
+                (secs(50), 5)
+            ])
+        );
+
+        let values = |range: Option<Vec<(Duration, u64)>>| -> Vec<u64> {
+            range.unwrap().into_iter().map(|(_, value)| value).collect()
+        };
+        assert_eq!(values(buffer.range_by_time(secs(30)..secs(50))), vec![3, 4]);
+        assert_eq!(
+            values(buffer.range_by_time(secs(25)..=secs(50))),
+            vec![3, 4, 5]
+        );
+        assert_eq!(values(buffer.range_by_time(..secs(30))), vec![2]);
+        assert_eq!(values(buffer.range_by_time(secs(60)..)), vec![]);
+        assert_eq!(values(buffer.range_by_time(secs(40)..secs(30))), vec![]);
+
+        let guard = buffer.read();
+        let newest: Vec<_> = guard.iter_with_time().unwrap().rev().take(2).collect();
+        assert_eq!(newest, vec![(secs(50), &5), (secs(40), &4)]);
+    }
+
+    #[test]
+    fn without_clock() {
+        let buffer = Buffer::new(2);
+        buffer.push_slice(&[1, 2, 3]);
+        assert_eq!(buffer.oldest_time(), None);
+        assert_eq!(buffer.newest_time(), None);
+        assert_eq!(buffer.snapshot_with_time(), None);
+        assert_eq!(buffer.range_by_time(..secs(1)), None);
+        assert!(buffer.read().iter_with_time().is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn wall_clock() {
+        let buffer = Buffer::builder(2).clock(WallClock::new()).build();
+        buffer.push(1);
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        assert!(buffer.newest_time().unwrap() <= since_epoch);
+        assert!(buffer.newest_time().unwrap() > since_epoch - secs(60));
+    }
+}
@@ -0,0 +1,197 @@
+#[cfg(feature = "std")]
+use std::sync::PoisonError;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use super::*;
+
+/// A point-in-time view of a buffer, handed to `wait_until` conditions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    pub len: usize,
+    pub capacity: usize,
+    pub fill_level: FillLevel,
+    /// Total number of values ever pushed.
+    pub pushed: u64,
+}
+
+#[cfg(feature = "std")]
+impl<T, L: RawLock> Shared<T, L> {
+    // The notify mutex is held from running the attempt until parked on the condvar,
+    // so a change in between cannot be missed. It is always taken before the buffer lock.
+    pub(crate) fn wait_for<R>(
+        &self,
+        mut attempt: impl FnMut() -> Option<R>,
+        deadline: Option<Instant>,
+    ) -> Option<R> {
+        let mut guard = self.notify.lock().unwrap_or_else(PoisonError::into_inner);
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        // pairs with the fence in `notify`, before the first attempt
+        fence(Ordering::SeqCst);
+        let result = loop {
+            if let Some(result) = attempt() {
+                break Some(result);
+            }
+            guard = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    if timeout.is_zero() {
+                        break None;
+                    }
+                    let result = self.condvar.wait_timeout(guard, timeout);
+                    result.unwrap_or_else(PoisonError::into_inner).0
+                }
+                None => self
+                    .condvar
+                    .wait(guard)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        };
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        result
+    }
+    pub(crate) fn wait_until(
+        &self,
+        mut condition: impl FnMut(&State) -> bool,
+        deadline: Option<Instant>,
+    ) -> Option<State> {
+        self.wait_for(
+            || {
+                let state = self.read().state();
+                condition(&state).then_some(state)
+            },
+            deadline,
+        )
+    }
+    fn wait_for_push(&self, deadline: Option<Instant>) -> Option<State> {
+        let pushed = self.read().pushed();
+        self.wait_until(|state| state.pushed > pushed, deadline)
+    }
+    fn wait_for_fill_level(&self, level: FillLevel, deadline: Option<Instant>) -> Option<State> {
+        self.wait_until(|state| state.fill_level == level, deadline)
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn deadline(timeout: Duration) -> Option<Instant> {
+    Instant::now().checked_add(timeout)
+}
+
+#[cfg(feature = "std")]
+impl<T, L: RawLock> Buffer<T, L> {
+    /// Blocks until at least one value has been pushed after this call.
+    pub fn wait_for_push(&self) -> State {
+        self.shared.wait_for_push(None).unwrap()
+    }
+    pub fn wait_for_push_timeout(&self, timeout: Duration) -> Option<State> {
+        self.shared.wait_for_push(deadline(timeout))
+    }
+    /// Blocks until `condition` holds. It is re-evaluated after every change to the buffer.
+    pub fn wait_until(&self, condition: impl FnMut(&State) -> bool) -> State {
+        self.shared.wait_until(condition, None).unwrap()
+    }
+    pub fn wait_until_timeout(
+        &self,
+        timeout: Duration,
+        condition: impl FnMut(&State) -> bool,
+    ) -> Option<State> {
+        self.shared.wait_until(condition, deadline(timeout))
+    }
+    pub fn wait_for_fill_level(&self, level: FillLevel) -> State {
+        self.shared.wait_for_fill_level(level, None).unwrap()
+    }
+    pub fn wait_for_fill_level_timeout(
+        &self,
+        level: FillLevel,
+        timeout: Duration,
+    ) -> Option<State> {
+        self.shared.wait_for_fill_level(level, deadline(timeout))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, L: RawLock> Reader<T, L> {
+    pub fn wait_for_push(&self) -> State {
+        self.shared.wait_for_push(None).unwrap()
+    }
+    pub fn wait_for_push_timeout(&self, timeout: Duration) -> Option<State> {
+        self.shared.wait_for_push(deadline(timeout))
+    }
+    pub fn wait_until(&self, condition: impl FnMut(&State) -> bool) -> State {
+        self.shared.wait_until(condition, None).unwrap()
+    }
+    pub fn wait_until_timeout(
+        &self,
+        timeout: Duration,
+        condition: impl FnMut(&State) -> bool,
+    ) -> Option<State> {
+        self.shared.wait_until(condition, deadline(timeout))
+    }
+    pub fn wait_for_fill_level(&self, level: FillLevel) -> State {
+        self.shared.wait_for_fill_level(level, None).unwrap()
+    }
+    pub fn wait_for_fill_level_timeout(
+        &self,
+        level: FillLevel,
+        timeout: Duration,
+    ) -> Option<State> {
+        self.shared.wait_for_fill_level(level, deadline(timeout))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn wait_for_push() {
+        let buffer = Buffer::new(4);
+        let reader = buffer.reader();
+
+        let consumer = thread::spawn(move || reader.wait_for_push());
+        while !consumer.is_finished() {
+            buffer.push(1);
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(consumer.join().unwrap().pushed >= 1);
+    }
+
+    #[test]
+    fn wait_for_fill_level() {
+        let buffer = Buffer::new(3);
+        let producer = buffer.writer();
+
+        let handle = thread::spawn(move || {
+            for value in 0..3 {
+                producer.push(value);
+            }
+        });
+        let state = buffer.wait_for_fill_level(FillLevel::Full);
+        assert_eq!(state.len, 3);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn timeouts() {
+        let buffer = Buffer::new(3);
+        assert_eq!(
+            buffer.wait_for_push_timeout(Duration::from_millis(10)),
+            None
+        );
+        assert_eq!(
+            buffer.wait_until_timeout(Duration::from_millis(10), |state| state.len > 0),
+            None
+        );
+
+        buffer.push_slice(&[1, 2]);
+        let state = buffer
+            .wait_until_timeout(Duration::from_millis(10), |state| state.len == 2)
+            .unwrap();
+        assert_eq!(state.fill_level, FillLevel::Partial);
+        assert!(buffer
+            .wait_for_fill_level_timeout(FillLevel::Full, Duration::from_millis(10))
+            .is_none());
+    }
+}